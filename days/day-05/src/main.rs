@@ -1,8 +1,6 @@
-use std::{
-    collections::{HashMap, HashSet, VecDeque},
-    fs,
-    time::Instant,
-};
+use std::{collections::HashSet, fs, time::Instant};
+
+use lib::{export::ExportOptions, graph::Graph, scan::pairs};
 
 type ParsedInput = (Vec<(u32, u32)>, Vec<Vec<u32>>);
 type Output = u32;
@@ -10,6 +8,16 @@ type Output = u32;
 fn main() {
     let input = input();
 
+    // Dump the rules instead of solving, all of them or only those between the pages of an
+    // update (by index), e.g. `cargo run -- --dot 3 | dot -Tsvg > day-05.svg`
+    let update = std::env::args().nth(2).and_then(|index| index.parse().ok());
+
+    match std::env::args().nth(1).as_deref() {
+        Some("--dot") => return print!("{}", rules_to_dot(&input, update)),
+        Some("--mermaid") => return print!("{}", rules_to_mermaid(&input, update)),
+        _ => {}
+    }
+
     let start = Instant::now();
     println!("Result (part 1): {} [{:?}]", part1(&input), start.elapsed());
 
@@ -26,14 +34,7 @@ fn input() -> ParsedInput {
 fn parse_input(input: &str) -> ParsedInput {
     let (rules_input, updates_input) = input.split_once("\n\n").unwrap();
 
-    let rules = rules_input
-        .lines()
-        .map(|line| {
-            let (left, right) = line.split_once("|").unwrap();
-
-            (left.parse().unwrap(), right.parse().unwrap())
-        })
-        .collect();
+    let rules = pairs(rules_input, "|").unwrap();
 
     let updates = updates_input
         .lines()
@@ -48,12 +49,12 @@ fn parse_input(input: &str) -> ParsedInput {
 }
 
 fn part1(input: &ParsedInput) -> Output {
-    let rule_index = build_rule_index(&input.0);
+    let rules = build_rules_graph(&input.0);
 
     input
         .1
         .iter()
-        .filter(|update| is_in_correct_order(update, &rule_index))
+        .filter(|update| is_in_correct_order(update, &rules))
         .map(|update| {
             let middle = update.len() / 2;
             update[middle]
@@ -62,15 +63,15 @@ fn part1(input: &ParsedInput) -> Output {
 }
 
 fn part2(input: &ParsedInput) -> Output {
-    let rule_index = build_rule_index(&input.0);
+    let rules = build_rules_graph(&input.0);
 
     let incorrect_updates = input
         .1
         .iter()
-        .filter(|update| !is_in_correct_order(update, &rule_index));
+        .filter(|update| !is_in_correct_order(update, &rules));
 
     incorrect_updates
-        .map(|update| fix_update_order(update, &rule_index))
+        .map(|update| fix_update_order(update, &rules))
         .map(|update| {
             let middle = update.len() / 2;
             update[middle]
@@ -78,116 +79,66 @@ fn part2(input: &ParsedInput) -> Output {
         .sum()
 }
 
-fn fix_update_order(update: &[u32], rule_index: &HashMap<u32, Vec<u32>>) -> Vec<u32> {
-    let all_pages = update.iter().copied().collect::<HashSet<u32>>();
-    let mut processed_pages = HashSet::<u32>::with_capacity(update.len());
-    let mut fixed_update = Vec::<u32>::with_capacity(update.len());
+fn fix_update_order(update: &[u32], rules: &Graph<u32>) -> Vec<u32> {
+    let pages = update.iter().copied().collect::<HashSet<u32>>();
 
-    let mut wrong_pages = VecDeque::<u32>::with_capacity(update.len());
-    let mut wrong_pages_swap = Vec::<u32>::with_capacity(update.len());
+    // Only the rules between the pages of the update matter (the full rule set loops)
+    let mut update_rules = rules.subgraph(|_, page| pages.contains(page));
 
     for page in update {
-        if !is_page_correct(page, &all_pages, &processed_pages, rule_index) {
-            wrong_pages.push_back(*page);
-        } else {
-            processed_pages.insert(*page);
-            fixed_update.push(*page);
-        }
-
-        wrong_pages_swap.clear();
-
-        while let Some(wrong_page) = wrong_pages.pop_front() {
-            if is_page_correct(&wrong_page, &all_pages, &processed_pages, rule_index) {
-                processed_pages.insert(wrong_page);
-                fixed_update.push(wrong_page);
-            } else {
-                wrong_pages_swap.push(wrong_page);
-            }
-        }
-
-        wrong_pages_swap
-            .iter()
-            .for_each(|p| wrong_pages.push_back(*p));
+        update_rules.add_node(*page);
     }
 
-    while !wrong_pages.is_empty() {
-        wrong_pages_swap.clear();
-
-        while let Some(wrong_page) = wrong_pages.pop_front() {
-            if is_page_correct(&wrong_page, &all_pages, &processed_pages, rule_index) {
-                processed_pages.insert(wrong_page);
-                fixed_update.push(wrong_page);
-            } else {
-                wrong_pages_swap.push(wrong_page);
-            }
-        }
-
-        wrong_pages_swap
-            .iter()
-            .for_each(|p| wrong_pages.push_back(*p));
-    }
-
-    fixed_update
+    update_rules
+        .toposort()
+        .expect("Rules of an update must not loop")
+        .into_iter()
+        .map(|id| *update_rules.node(id))
+        .collect()
 }
 
-fn is_page_correct(
-    page: &u32,
-    all_pages: &HashSet<u32>,
-    previous_pages: &HashSet<u32>,
-    rule_index: &HashMap<u32, Vec<u32>>,
-) -> bool {
-    let expected_previous_page = rule_index.get(page).and_then(|previous_pages| {
-        let existing_previous_pages: Vec<u32> = previous_pages
-            .iter()
-            .filter(|p| all_pages.contains(p))
-            .copied()
-            .collect();
-
-        if existing_previous_pages.is_empty() {
-            None
-        } else {
-            Some(existing_previous_pages)
-        }
-    });
-
-    if let Some(expected_previous_page) = expected_previous_page {
-        if expected_previous_page
-            .iter()
-            .any(|p| !previous_pages.contains(p))
-        {
-            return false;
-        }
+fn build_rules_graph(rules: &[(u32, u32)]) -> Graph<u32> {
+    let mut graph = Graph::directed();
+
+    for &(before, after) in rules {
+        graph.connect(before, after, ());
     }
 
-    true
+    graph
 }
 
-fn build_rule_index(rules: &Vec<(u32, u32)>) -> HashMap<u32, Vec<u32>> {
-    let mut index = HashMap::<u32, Vec<u32>>::new();
-
-    for &(left, right) in rules {
-        index
-            .entry(right)
-            .and_modify(|e| e.push(left))
-            .or_insert(vec![left]);
-    }
-
-    index
+fn rules_to_dot(input: &ParsedInput, update: Option<usize>) -> String {
+    export_graph(input, update).to_dot(&export_options())
 }
 
-fn is_in_correct_order(update: &Vec<u32>, rule_index: &HashMap<u32, Vec<u32>>) -> bool {
-    let all_pages = update.iter().copied().collect::<HashSet<u32>>();
-    let mut processed_pages = HashSet::<u32>::with_capacity(update.len());
+fn rules_to_mermaid(input: &ParsedInput, update: Option<usize>) -> String {
+    export_graph(input, update).to_mermaid(&export_options())
+}
 
-    for page in update {
-        if !is_page_correct(page, &all_pages, &processed_pages, rule_index) {
-            return false;
-        }
+fn export_graph(input: &ParsedInput, update: Option<usize>) -> Graph<u32> {
+    let rules = build_rules_graph(&input.0);
 
-        processed_pages.insert(*page);
+    match update.and_then(|index| input.1.get(index)) {
+        Some(update) => rules.subgraph(|_, page| update.contains(page)),
+        None => rules,
     }
+}
 
-    true
+fn export_options() -> ExportOptions<'static, u32, ()> {
+    ExportOptions::new(|page: &u32| page.to_string())
+}
+
+fn is_in_correct_order(update: &[u32], rules: &Graph<u32>) -> bool {
+    update.iter().enumerate().all(|(i, page)| {
+        let Some(id) = rules.node_id(page) else {
+            return true;
+        };
+
+        // No previous page must be required after this one
+        rules
+            .neighboors(id)
+            .all(|after| !update[..i].contains(rules.node(after)))
+    })
 }
 
 #[cfg(test)]
@@ -228,6 +179,18 @@ mod tests {
         parse_input(input)
     }
 
+    #[test]
+    fn test_export() {
+        let input = input();
+
+        // 75,29,13
+        assert_eq!(
+            rules_to_mermaid(&input, Some(2)),
+            "flowchart LR\n    n0[\"13\"]\n    n1[\"75\"]\n    n2[\"29\"]\n    n1 --> n2\n    n1 --> n0\n    n2 --> n0\n"
+        );
+        assert_eq!(rules_to_dot(&input, None).matches(" -> ").count(), 21);
+    }
+
     #[test]
     fn test_part1() {
         println!("{:?}", input());
@@ -1,16 +1,17 @@
 use core::panic;
-use std::{collections::HashSet, fs, time::Instant};
+use std::{fs, time::Instant};
+
+use lib::{Point, PointSet};
 
 type ParsedInput = (Point, Map);
 type Output = u32;
 
 type Map = Vec<Vec<Tile>>;
-type Point = (i32, i32);
 
-const UP: Point = (-1, 0);
-const DOWN: Point = (1, 0);
-const LEFT: Point = (0, -1);
-const RIGHT: Point = (0, 1);
+const UP: Point = Point { x: -1, y: 0 };
+const DOWN: Point = Point { x: 1, y: 0 };
+const LEFT: Point = Point { x: 0, y: -1 };
+const RIGHT: Point = Point { x: 0, y: 1 };
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 enum Tile {
@@ -45,7 +46,7 @@ fn parse_input(input: &str) -> ParsedInput {
                 .map(|(j, c)| match c {
                     '#' => Tile::Block,
                     '^' => {
-                        starting_pos = (i as i32, j as i32);
+                        starting_pos = (i as i64, j as i64).into();
                         Tile::Free
                     }
                     _ => Tile::Free,
@@ -61,32 +62,29 @@ fn part1(input: &ParsedInput) -> Output {
     do_patrol(input).len() as u32
 }
 
-fn do_patrol(input: &ParsedInput) -> HashSet<Point> {
+fn do_patrol(input: &ParsedInput) -> PointSet {
     let mut direction = UP;
     let mut current_pos = input.0;
     let map = &input.1;
 
-    let mut traversed_tiles: HashSet<Point> = HashSet::new();
-    traversed_tiles.insert(current_pos);
+    let mut traversed_tiles = PointSet::new(map_size(map));
+    traversed_tiles
+        .insert(current_pos)
+        .expect("The guard starts on the map");
 
     loop {
-        let (next_pos, tile, steps) = move_to_next_obstacle(current_pos, direction, map);
-
-        traversed_tiles.extend(steps.iter());
+        let (next_pos, tile) = move_to_next_obstacle(current_pos, direction, map, |pos| {
+            traversed_tiles
+                .insert(pos)
+                .expect("Only tiles of the map are traversed");
+        });
 
         if tile.is_none() {
             break;
         }
 
         current_pos = next_pos;
-
-        direction = match direction {
-            UP => RIGHT,
-            RIGHT => DOWN,
-            DOWN => LEFT,
-            LEFT => UP,
-            _ => panic!("Unsupported"),
-        };
+        direction = turn_right(direction);
     }
 
     traversed_tiles
@@ -96,94 +94,110 @@ fn part2(input: &ParsedInput) -> Output {
     let patrol_path = do_patrol(input);
 
     let starting_pos = input.0;
-    let map = &input.1;
+    let mut map = input.1.clone();
+    let mut hits = [UP, RIGHT, DOWN, LEFT].map(|_| PointSet::new(map_size(&map)));
     let mut looping_config = 0;
 
-    for tile_pos in patrol_path {
+    for tile_pos in patrol_path.iter() {
         if tile_pos == starting_pos {
             continue;
         }
 
-        if let Some(Tile::Free) = tile_at(tile_pos, map) {
-            let mut new_map = map.clone();
-            new_map[tile_pos.0 as usize][tile_pos.1 as usize] = Tile::Block;
+        if let Some(Tile::Free) = tile_at(tile_pos, &map) {
+            map[tile_pos.x as usize][tile_pos.y as usize] = Tile::Block;
 
-            if is_patrol_looping(starting_pos, &new_map) {
+            if is_patrol_looping(starting_pos, &map, &mut hits) {
                 looping_config += 1;
             }
+
+            map[tile_pos.x as usize][tile_pos.y as usize] = Tile::Free;
         }
     }
 
     looping_config
 }
 
-fn is_patrol_looping(starting_pos: Point, map: &Map) -> bool {
-    let mut direction = UP;
-    let mut current_pos = starting_pos;
+// The patrol loops when it hits an obstacle from the same position and direction twice.
+// `hits` holds those positions for each direction, cleared before use.
+fn is_patrol_looping(starting_pos: Point, map: &Map, hits: &mut [PointSet; 4]) -> bool {
+    hits.iter_mut().for_each(PointSet::clear);
 
-    let mut obstacle_hit_index: HashSet<(Point, Point)> = HashSet::new();
+    let (mut pos, mut direction) = (starting_pos, UP);
 
     loop {
-        let (next_pos, tile, _) = move_to_next_obstacle(current_pos, direction, map);
+        let (next_pos, tile) = move_to_next_obstacle(pos, direction, map, |_| {});
 
         if tile.is_none() {
             return false;
         }
 
-        if obstacle_hit_index.contains(&(next_pos, direction)) {
+        if !hits[direction_index(direction)]
+            .insert(next_pos)
+            .expect("Obstacles are hit from the map")
+        {
             return true;
         }
 
-        obstacle_hit_index.insert((next_pos, direction));
+        pos = next_pos;
+        direction = turn_right(direction);
+    }
+}
 
-        current_pos = next_pos;
+fn direction_index(direction: Point) -> usize {
+    match direction {
+        UP => 0,
+        RIGHT => 1,
+        DOWN => 2,
+        LEFT => 3,
+        _ => panic!("Unsupported"),
+    }
+}
 
-        direction = match direction {
-            UP => RIGHT,
-            RIGHT => DOWN,
-            DOWN => LEFT,
-            LEFT => UP,
-            _ => panic!("Unsupported"),
-        };
+fn turn_right(direction: Point) -> Point {
+    match direction {
+        UP => RIGHT,
+        RIGHT => DOWN,
+        DOWN => LEFT,
+        LEFT => UP,
+        _ => panic!("Unsupported"),
     }
 }
 
+// `traverse` is called on every tile walked on, the last one included
 fn move_to_next_obstacle(
     pos: Point,
     direction: Point,
     map: &Map,
-) -> (Point, Option<Tile>, HashSet<Point>) {
+    mut traverse: impl FnMut(Point),
+) -> (Point, Option<Tile>) {
     let mut current_pos = pos;
-    let mut traversed_tiles: HashSet<Point> = HashSet::new();
     loop {
-        let next_pos = move_to(current_pos, direction);
+        let next_pos = current_pos + direction;
         let tile = tile_at(next_pos, map);
 
-        traversed_tiles.insert(current_pos);
+        traverse(current_pos);
 
         match tile {
             Some(Tile::Free) => {
                 current_pos = next_pos;
             }
-            Some(Tile::Block) => return (current_pos, Some(Tile::Block), traversed_tiles),
-            None => return (current_pos, None, traversed_tiles),
+            Some(Tile::Block) => return (current_pos, Some(Tile::Block)),
+            None => return (current_pos, None),
         }
     }
 }
 
-fn move_to(pos: Point, direction: Point) -> Point {
-    (pos.0 + direction.0, pos.1 + direction.1)
+fn map_size(map: &Map) -> (usize, usize) {
+    (map.len(), map.first().map(|v| v.len()).unwrap_or(0))
 }
 
 fn tile_at(pos: Point, map: &Map) -> Option<Tile> {
-    if pos.0 < 0
-        || pos.1 < 0
-        || pos.0 >= map.len() as i32
-        || pos.1 >= map.first().map(|v| v.len()).unwrap_or(0) as i32
-    {
+    let (height, width) = map_size(map);
+
+    if pos.x < 0 || pos.y < 0 || pos.x >= height as i64 || pos.y >= width as i64 {
         None
     } else {
-        Some(map[pos.0 as usize][pos.1 as usize])
+        Some(map[pos.x as usize][pos.y as usize])
     }
 }
 
@@ -1,4 +1,6 @@
-use std::{collections::HashMap, fs, ops::Div, time::Instant};
+use std::{collections::HashMap, fs, time::Instant};
+
+use lib::math::{n_digit, split_int};
 
 type ParsedInput = Vec<u64>;
 type Output = u64;
@@ -34,22 +36,10 @@ fn part2(input: &ParsedInput) -> Output {
     blink_stones(input, 75)
 }
 
-fn split_int(n: u64) -> (u64, u64) {
-    let n_half_size = (n.ilog10() + 1).div(2);
-    let left_part = n.div(10u64.pow(n_half_size));
-    let right_part = n - left_part * 10u64.pow(n_half_size);
-
-    (left_part, right_part)
-}
-
-fn n_digit(n: u64) -> u32 {
-    n.ilog10() + 1
-}
-
 fn blink_stone(n: u64) -> (u64, Option<u64>) {
     if n == 0 {
         (1, None)
-    } else if n_digit(n) % 2 == 0 {
+    } else if n_digit(n).is_multiple_of(2) {
         let (a, b) = split_int(n);
 
         (a, Some(b))
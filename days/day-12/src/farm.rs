@@ -1,6 +1,6 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, VecDeque};
 
-use lib::{debugln, Grid2d, Point, CARDINAL_DIRECTIONS};
+use lib::{debugln, Grid2d, Point, PointSet, CARDINAL_DIRECTIONS};
 
 struct Region(char, HashMap<Point, usize>);
 
@@ -82,7 +82,7 @@ impl Farm {
     fn compute_regions(&mut self) {
         self.regions.clear();
 
-        let mut visited: HashSet<Point> = HashSet::new();
+        let mut visited = PointSet::for_grid(&self.land);
 
         for (i, line) in self.land.iter() {
             for (j, &plot) in line.iter() {
@@ -95,7 +95,7 @@ impl Farm {
                 let region = self.find_region(plot, pos);
 
                 region.iter().for_each(|(c, _)| {
-                    visited.insert(*c);
+                    visited.insert(*c).expect("Regions are within the land");
                 });
 
                 self.regions.push(Region(plot, region));
@@ -1,12 +1,18 @@
+use lib::linear::{minimize_cost, IntegerSolution};
+
 pub struct Point {
     pub x: i64,
     pub y: i64,
 }
 
+pub struct Button {
+    pub movement: Point,
+    pub tokens: i64,
+}
+
 pub struct ClawMachine {
     prize: Point,
-    a: Point,
-    b: Point,
+    buttons: Vec<Button>,
 }
 
 impl From<(i64, i64)> for Point {
@@ -19,69 +25,36 @@ impl ClawMachine {
     pub const A_TOKEN: i64 = 3;
     pub const B_TOKEN: i64 = 1;
     const CONVERSION: i64 = 10_000_000_000_000;
+    const MAX_PRESSES: i64 = 100;
 
-    pub fn new(prize: Point, a: Point, b: Point) -> Self {
-        ClawMachine { prize, a, b }
+    pub fn new(prize: Point, buttons: Vec<Button>) -> Self {
+        ClawMachine { prize, buttons }
     }
 
-    pub fn solve(&self) -> Option<Point> {
-        self.solve_for(&self.prize).filter(|solution| {
-            if solution.x > 100 || solution.y > 100 {
-                println!("Out-of-range solution");
-                return false;
-            }
-
-            true
-        })
+    pub fn solve(&self) -> Option<IntegerSolution> {
+        self.solve_for(&self.prize, Some(Self::MAX_PRESSES))
     }
 
-    pub fn solve_with_converstion(&self) -> Option<Point> {
+    pub fn solve_with_conversion(&self) -> Option<IntegerSolution> {
         self.solve_for(
             &(
                 self.prize.x + Self::CONVERSION,
                 self.prize.y + Self::CONVERSION,
             )
                 .into(),
+            None,
         )
-        .filter(|solution| {
-            if solution.x < 100 || solution.y < 100 {
-                println!("Out-of-range solution");
-                return false;
-            }
-
-            true
-        })
     }
 
-    fn solve_for(&self, target: &Point) -> Option<Point> {
-        let b_prime = self.a.x * target.y - self.a.y * target.x;
-        let factor = self.a.x * self.b.y - self.a.y * self.b.x;
-
-        if b_prime % factor != 0 {
-            println!("Non-integer solution");
-            return None;
-        }
-
-        let b = b_prime / factor;
-        let a_prime = target.x - self.b.x * b;
-
-        if a_prime % self.a.x != 0 {
-            println!("Non-interger solution");
-            return None;
-        }
-
-        let a = a_prime / self.a.x;
-
-        if a < 0 || b < 0 {
-            println!("Out-of-range solution");
-            return None;
-        }
-
-        println!(
-            "Solution found for (X={}, Y={}): ({}, {})",
-            target.x, target.y, a, b
-        );
+    // Each button is a column of the system: sum(presses_i * button_i) = target
+    fn solve_for(&self, target: &Point, max_presses: Option<i64>) -> Option<IntegerSolution> {
+        let system = vec![
+            self.buttons.iter().map(|b| b.movement.x).collect(),
+            self.buttons.iter().map(|b| b.movement.y).collect(),
+        ];
+        let tokens: Vec<i64> = self.buttons.iter().map(|b| b.tokens).collect();
 
-        Some((a, b).into())
+        // Buttons only move forward, so the search is bounded
+        minimize_cost(&system, &[target.x, target.y], &tokens, max_presses).ok()
     }
 }
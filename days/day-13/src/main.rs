@@ -1,7 +1,7 @@
 use std::{fs, time::Instant};
 
-use game::ClawMachine;
-use regex::Regex;
+use game::{Button, ClawMachine};
+use lib::{scan, scan::sections};
 
 mod game;
 
@@ -24,39 +24,28 @@ fn input() -> ParsedInput {
 }
 
 fn parse_input(input: &str) -> ParsedInput {
-    let button_regex = Regex::new(r"^Button .: X(?<x>(\-|\+\d+)), Y(?<y>(\-|\+)\d+)$").unwrap();
-    let prize_regex = Regex::new(r"^Prize: X=(?<x>\d+), Y=(?<y>\d+)$").unwrap();
-
-    input
-        .split("\n\n")
+    sections(input)
         .map(|block| {
-            let lines: Vec<&str> = block.split("\n").collect();
-
-            let Some(button_a) = button_regex.captures(lines[0]) else {
-                panic!("Invalid input")
-            };
-
-            let Some(button_b) = button_regex.captures(lines[1]) else {
-                panic!("Invalid input")
-            };
-
-            let Some(prize) = prize_regex.captures(lines[2]) else {
-                panic!("Invalid input")
-            };
-
-            ClawMachine::new(
-                (prize["x"].parse().unwrap(), prize["y"].parse().unwrap()).into(),
-                (
-                    button_a["x"].parse().unwrap(),
-                    button_a["y"].parse().unwrap(),
-                )
-                    .into(),
-                (
-                    button_b["x"].parse().unwrap(),
-                    button_b["y"].parse().unwrap(),
-                )
-                    .into(),
-            )
+            let lines: Vec<&str> = block.lines().collect();
+
+            let buttons = [
+                (lines[0], ClawMachine::A_TOKEN),
+                (lines[1], ClawMachine::B_TOKEN),
+            ]
+            .into_iter()
+            .map(|(line, tokens)| {
+                let (_, x, y) = scan!(line, "Button {}: X{}, Y{}", char, i64, i64).unwrap();
+
+                Button {
+                    movement: (x, y).into(),
+                    tokens,
+                }
+            })
+            .collect();
+
+            let prize = scan!(lines[2], "Prize: X={}, Y={}", i64, i64).unwrap();
+
+            ClawMachine::new(prize.into(), buttons)
         })
         .collect()
 }
@@ -65,15 +54,15 @@ fn part1(input: &ParsedInput) -> Output {
     input
         .iter()
         .filter_map(|g| g.solve())
-        .map(|s| (s.x * ClawMachine::A_TOKEN + s.y * ClawMachine::B_TOKEN) as u64)
+        .map(|s| s.cost as u64)
         .sum()
 }
 
 fn part2(input: &ParsedInput) -> Output {
     input
         .iter()
-        .filter_map(|g| g.solve_with_converstion())
-        .map(|s| (s.x * ClawMachine::A_TOKEN + s.y * ClawMachine::B_TOKEN) as u64)
+        .filter_map(|g| g.solve_with_conversion())
+        .map(|s| s.cost as u64)
         .sum()
 }
 
@@ -111,6 +100,6 @@ Prize: X=18641, Y=10279
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&input()), 0);
+        assert_eq!(part2(&input()), 875318608908);
     }
 }
@@ -1,5 +1,7 @@
 use core::f64;
-use std::{collections::HashMap, num::ParseIntError};
+use std::collections::HashMap;
+
+use lib::{math::crt, scan, scan::ScanError};
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Point {
@@ -13,15 +15,6 @@ impl From<(i64, i64)> for Point {
     }
 }
 
-impl Point {
-    pub fn parse((x, y): (&str, &str)) -> Result<Self, ParseIntError> {
-        Ok(Point {
-            x: x.parse()?,
-            y: y.parse()?,
-        })
-    }
-}
-
 #[derive(Copy, Clone)]
 pub struct Robot {
     starting_position: Point,
@@ -29,17 +22,12 @@ pub struct Robot {
 }
 
 impl Robot {
-    pub fn parse(input: &str) -> Result<Self, String> {
-        let (pos_str, veolicity_str) = input.split_once(" ").ok_or("Invalid input")?;
-
-        let pos = pos_str.split_once("=").ok_or("Invalid position")?.1;
-        let velocity = veolicity_str.split_once("=").ok_or("Invalid velocity")?.1;
+    pub fn parse(input: &str) -> Result<Self, ScanError> {
+        let (x, y, v_x, v_y) = scan!(input, "p={},{} v={},{}", i64, i64, i64, i64)?;
 
         Ok(Robot {
-            starting_position: Point::parse(pos.split_once(",").ok_or("Invalid position data")?)
-                .map_err(|_| "Invalid position value".to_owned())?,
-            velocity: Point::parse(velocity.split_once(",").ok_or("Invalid velocity data")?)
-                .map_err(|_| "Invalid velocity value".to_owned())?,
+            starting_position: (x, y).into(),
+            velocity: (v_x, v_y).into(),
         })
     }
 }
@@ -114,6 +102,7 @@ impl Grid {
         }
     }
 
+    #[allow(dead_code)]
     fn debug(&self, robot_positions: &[Point]) {
         // println!("{:?}", robot_positions);
 
@@ -137,29 +126,36 @@ impl Grid {
         println!();
     }
 
+    // Robots x position cycles every size.x seconds (and y every size.y) so we search the low
+    // variance time on each axis independently and combine both with the CRT
     pub fn find_christmas_tree(&self) -> u32 {
-        for n in 0..1_000_000 {
-            let robot_positions = self.robot_positions_after(n);
-
-            let all_x: Vec<f64> = robot_positions.iter().map(|p| p.x as f64).collect();
-            let all_y: Vec<f64> = robot_positions.iter().map(|p| p.y as f64).collect();
-
-            let mean_x: f64 = all_x.iter().sum::<f64>() / all_x.len() as f64;
-            let mean_y: f64 = all_y.iter().sum::<f64>() / all_y.len() as f64;
-
-            let v_x = all_x.iter().map(|x| (x - mean_x).powi(2)).sum::<f64>() / all_x.len() as f64;
-            let v_y = all_y.iter().map(|y| (y - mean_y).powi(2)).sum::<f64>() / all_y.len() as f64;
-
-            if v_x < 400f64 && v_y < 400f64 {
-                // self.debug(&robot_positions);
-
-                // println!("Grid: {}", n);
-                // println!("Variance: X={}, Y={}", v_x, v_y);
+        let low_variance_time = |period: i64, axis: fn(&Point) -> i64| {
+            (0..period).find(|n| {
+                let values: Vec<f64> = self
+                    .robot_positions_after(*n)
+                    .iter()
+                    .map(|p| axis(p) as f64)
+                    .collect();
+
+                variance(&values) < 400f64
+            })
+        };
+
+        let (Some(x_time), Some(y_time)) = (
+            low_variance_time(self.size.x, |p| p.x),
+            low_variance_time(self.size.y, |p| p.y),
+        ) else {
+            return 0;
+        };
+
+        crt(&[(x_time, self.size.x), (y_time, self.size.y)])
+            .map(|(n, _)| n as u32)
+            .unwrap_or(0)
+    }
+}
 
-                return n as u32;
-            }
-        }
+fn variance(values: &[f64]) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
 
-        0
-    }
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
 }
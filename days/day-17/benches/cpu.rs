@@ -0,0 +1,49 @@
+// Interpreted vs compiled runs of a part 2 like program: `cargo bench -p day-17`
+use std::{hint::black_box, time::Instant};
+
+use day_17::cpu::{Cpu, Registers};
+
+const PROGRAM: &str = "Register A: 0
+Register B: 0
+Register C: 0
+
+Program: 2,4,1,3,7,5,0,3,1,5,4,1,5,5,3,0";
+
+const RUNS: u64 = 200_000;
+// 16 outputs per run, like the part 2 candidates
+const START_A: u64 = 1 << 45;
+
+fn main() {
+    let mut cpu = Cpu::parse(PROGRAM).unwrap();
+    let compiled = cpu.compile();
+    let registers = cpu.registers();
+
+    let start = Instant::now();
+    let interpreted: Vec<Vec<u8>> = (START_A..START_A + RUNS)
+        .map(|a| {
+            cpu.reset();
+            cpu.set_registers(Registers {
+                a: black_box(a),
+                ..registers
+            });
+            cpu.run().unwrap()
+        })
+        .collect();
+    let interpreted_time = start.elapsed();
+
+    let start = Instant::now();
+    let compiled_outputs: Vec<Vec<u8>> = (START_A..START_A + RUNS)
+        .map(|a| compiled.run_for_a(black_box(a)).unwrap())
+        .collect();
+    let compiled_time = start.elapsed();
+
+    assert_eq!(interpreted, compiled_outputs);
+
+    println!("{} runs", RUNS);
+    println!("Interpreted: {:?}", interpreted_time);
+    println!("Compiled:    {:?}", compiled_time);
+    println!(
+        "Speedup:     {:.1}x",
+        interpreted_time.as_secs_f64() / compiled_time.as_secs_f64()
+    );
+}
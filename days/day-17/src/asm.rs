@@ -0,0 +1,255 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Adv,
+    Bxl,
+    Bst,
+    Jnz,
+    Bxc,
+    Out,
+    Bdv,
+    Cdv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandKind {
+    Literal,
+    Combo,
+    // Read but not used (BXC)
+    Ignored,
+}
+
+const OPCODES: [Opcode; 8] = [
+    Opcode::Adv,
+    Opcode::Bxl,
+    Opcode::Bst,
+    Opcode::Jnz,
+    Opcode::Bxc,
+    Opcode::Out,
+    Opcode::Bdv,
+    Opcode::Cdv,
+];
+
+impl Opcode {
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Opcode::Adv => "adv",
+            Opcode::Bxl => "bxl",
+            Opcode::Bst => "bst",
+            Opcode::Jnz => "jnz",
+            Opcode::Bxc => "bxc",
+            Opcode::Out => "out",
+            Opcode::Bdv => "bdv",
+            Opcode::Cdv => "cdv",
+        }
+    }
+
+    pub fn operand_kind(&self) -> OperandKind {
+        match self {
+            Opcode::Bxl | Opcode::Jnz => OperandKind::Literal,
+            Opcode::Bxc => OperandKind::Ignored,
+            _ => OperandKind::Combo,
+        }
+    }
+
+    fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        OPCODES.into_iter().find(|op| op.mnemonic() == mnemonic)
+    }
+}
+
+impl TryFrom<u8> for Opcode {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        OPCODES.get(value as usize).copied().ok_or(value)
+    }
+}
+
+impl From<Opcode> for u8 {
+    fn from(opcode: Opcode) -> Self {
+        opcode as u8
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AsmError {
+    // 1-based
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for AsmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+// One instruction per line, combo operands are shown as the value they read: `adv a`, `out b`.
+// Ignored operands are only shown when not 0 so the listing assembles back to the same program.
+// Anything that can't be written as an instruction (invalid opcode, operand above 7, missing
+// operand) is shown as raw bytes: `.byte 8 1`.
+pub fn disassemble(program: &[u8]) -> String {
+    program
+        .chunks(2)
+        .map(|instruction| {
+            let (Ok(opcode), Some(&operand @ 0..=7)) =
+                (Opcode::try_from(instruction[0]), instruction.get(1))
+            else {
+                let bytes: Vec<String> = instruction.iter().map(|b| b.to_string()).collect();
+
+                return format!(".byte {}", bytes.join(" "));
+            };
+
+            match (opcode.operand_kind(), operand) {
+                (OperandKind::Ignored, 0) => opcode.mnemonic().to_string(),
+                (kind, operand) => {
+                    format!("{} {}", opcode.mnemonic(), format_operand(kind, operand))
+                }
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn format_operand(kind: OperandKind, operand: u8) -> String {
+    match (kind, operand) {
+        (OperandKind::Combo, 4) => "a".to_string(),
+        (OperandKind::Combo, 5) => "b".to_string(),
+        (OperandKind::Combo, 6) => "c".to_string(),
+        _ => operand.to_string(),
+    }
+}
+
+// Reverse of `disassemble`, `;` starts a comment
+pub fn assemble(source: &str) -> Result<Vec<u8>, AsmError> {
+    let mut program = vec![];
+
+    for (i, line) in source.lines().enumerate() {
+        let error = |message: String| AsmError {
+            line: i + 1,
+            message,
+        };
+
+        let line = line.split(';').next().unwrap().trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let mnemonic = parts.next().unwrap().to_lowercase();
+
+        if mnemonic == ".byte" {
+            for byte in parts {
+                program.push(
+                    byte.parse()
+                        .map_err(|_| error(format!("Invalid byte {:?}", byte)))?,
+                );
+            }
+
+            continue;
+        }
+
+        let opcode = Opcode::from_mnemonic(&mnemonic)
+            .ok_or_else(|| error(format!("Unknown instruction {:?}", mnemonic)))?;
+
+        let operand = match (parts.next(), opcode.operand_kind()) {
+            (None, OperandKind::Ignored) => 0,
+            (None, _) => return Err(error(format!("Missing operand for {}", mnemonic))),
+            (Some(operand), kind) => parse_operand(kind, operand)
+                .ok_or_else(|| error(format!("Invalid operand {:?} for {}", operand, mnemonic)))?,
+        };
+
+        if let Some(extra) = parts.next() {
+            return Err(error(format!("Unexpected {:?}", extra)));
+        }
+
+        program.push(opcode.into());
+        program.push(operand);
+    }
+
+    Ok(program)
+}
+
+fn parse_operand(kind: OperandKind, operand: &str) -> Option<u8> {
+    match (kind, operand.to_lowercase().as_str()) {
+        (OperandKind::Combo, "a") => Some(4),
+        (OperandKind::Combo, "b") => Some(5),
+        (OperandKind::Combo, "c") => Some(6),
+        (_, n) => n.parse().ok().filter(|n| *n < 8),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let program = vec![2, 4, 1, 3, 7, 5, 0, 3, 1, 5, 4, 1, 5, 5, 3, 0];
+        let source = disassemble(&program);
+
+        assert_eq!(
+            source,
+            "bst a\nbxl 3\ncdv b\nadv 3\nbxl 5\nbxc 1\nout b\njnz 0"
+        );
+        assert_eq!(assemble(&source), Ok(program));
+
+        assert_eq!(
+            assemble("adv 1 ; A /= 2\n\nout a\nbxc\njnz 0"),
+            Ok(vec![0, 1, 5, 4, 4, 0, 3, 0])
+        );
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert_eq!(assemble("adv 1\nmul 2").unwrap_err().line, 2);
+        assert_eq!(assemble("bxl a").unwrap_err().line, 1);
+        assert_eq!(assemble("out").unwrap_err().line, 1);
+        assert_eq!(assemble("out 8").unwrap_err().line, 1);
+        assert_eq!(assemble(".byte 256").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn test_invalid_opcodes() {
+        let program = vec![0, 1, 8, 3, 9];
+        let source = disassemble(&program);
+
+        assert_eq!(source, "adv 1\n.byte 8 3\n.byte 9");
+        assert_eq!(assemble(&source), Ok(program));
+
+        let program = vec![1, 9, 4, 200, 5, 7, 2];
+        let source = disassemble(&program);
+
+        assert_eq!(source, ".byte 1 9\n.byte 4 200\nout 7\n.byte 2");
+        assert_eq!(assemble(&source), Ok(program));
+    }
+
+    #[test]
+    fn test_round_trip_any_bytes() {
+        for instruction in 0..=u16::MAX {
+            let program = instruction.to_be_bytes().to_vec();
+
+            assert_eq!(assemble(&disassemble(&program)), Ok(program));
+        }
+
+        // xorshift64, fixed seed so failures are reproducible
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..1000 {
+            let len = next() % 32;
+            let program: Vec<u8> = (0..len).map(|_| (next() % 12) as u8).collect();
+
+            assert_eq!(assemble(&disassemble(&program)), Ok(program));
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{
+    asm::Opcode,
+    cpu::{Cpu, CpuError, Registers},
+};
+
+#[derive(Debug, Clone, Copy)]
+enum Combo {
+    Value(u64),
+    A,
+    B,
+    C,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Adv(Combo),
+    Bxl(u64),
+    Bst(Combo),
+    Jnz(usize),
+    Bxc,
+    Out(Combo),
+    Bdv(Combo),
+    Cdv(Combo),
+    // Index in `errors`
+    Invalid(usize),
+}
+
+// Program decoded once, operands resolved ahead of time. Every pc is decoded (not only even
+// ones) as `jnz` can jump anywhere, decoding errors are kept until the instruction is executed
+// so it fails exactly like `Cpu::run`.
+#[derive(Debug, Clone)]
+pub struct CompiledProgram {
+    ops: Vec<Op>,
+    errors: Vec<CpuError>,
+    registers: Registers,
+}
+
+impl Cpu {
+    pub fn compile(&self) -> CompiledProgram {
+        let program = self.program();
+        let mut errors = vec![];
+
+        let ops = (0..program.len())
+            .map(|pc| {
+                compile_op(program, pc).unwrap_or_else(|error| {
+                    errors.push(error);
+                    Op::Invalid(errors.len() - 1)
+                })
+            })
+            .collect();
+
+        CompiledProgram {
+            ops,
+            errors,
+            registers: self.registers(),
+        }
+    }
+}
+
+fn compile_op(program: &[u8], pc: usize) -> Result<Op, CpuError> {
+    let opcode =
+        Opcode::try_from(program[pc]).map_err(|opcode| CpuError::InvalidOpcode { pc, opcode })?;
+    let operand = *program.get(pc + 1).ok_or(CpuError::MissingOperand { pc })?;
+
+    let combo = || match operand {
+        0..=3 => Ok(Combo::Value(operand as u64)),
+        4 => Ok(Combo::A),
+        5 => Ok(Combo::B),
+        6 => Ok(Combo::C),
+        _ => Err(CpuError::InvalidComboOperand { pc, operand }),
+    };
+
+    Ok(match opcode {
+        Opcode::Adv => Op::Adv(combo()?),
+        Opcode::Bxl => Op::Bxl(operand as u64),
+        Opcode::Bst => Op::Bst(combo()?),
+        Opcode::Jnz => Op::Jnz(operand as usize),
+        Opcode::Bxc => Op::Bxc,
+        Opcode::Out => Op::Out(combo()?),
+        Opcode::Bdv => Op::Bdv(combo()?),
+        Opcode::Cdv => Op::Cdv(combo()?),
+    })
+}
+
+impl CompiledProgram {
+    // Initial registers of the compiled Cpu
+    pub fn registers(&self) -> Registers {
+        self.registers
+    }
+
+    // Same as `Cpu::run` with register A replaced
+    pub fn run_for_a(&self, a: u64) -> Result<Vec<u8>, CpuError> {
+        self.run(
+            Registers {
+                a,
+                ..self.registers
+            },
+            None,
+        )
+    }
+
+    pub fn run(&self, registers: Registers, step_limit: Option<u64>) -> Result<Vec<u8>, CpuError> {
+        let mut stdout = Vec::new();
+
+        self.execute(registers, step_limit, |value| {
+            stdout.push(value);
+            true
+        })?;
+
+        Ok(stdout)
+    }
+
+    // Stops as soon as the output diverges from `target` (and returns the output so far)
+    pub fn run_matching(
+        &self,
+        registers: Registers,
+        target: &[u8],
+        step_limit: Option<u64>,
+    ) -> Result<Vec<u8>, CpuError> {
+        let mut stdout = Vec::new();
+
+        self.execute(registers, step_limit, |value| {
+            stdout.push(value);
+            target.starts_with(&stdout)
+        })?;
+
+        Ok(stdout)
+    }
+
+    // `out` returns false to stop the program
+    fn execute(
+        &self,
+        registers: Registers,
+        step_limit: Option<u64>,
+        mut out: impl FnMut(u8) -> bool,
+    ) -> Result<(), CpuError> {
+        let Registers {
+            mut a,
+            mut b,
+            mut c,
+        } = registers;
+        let mut pc = 0;
+        let mut steps = 0;
+        let step_limit = step_limit.unwrap_or(u64::MAX);
+
+        while let Some(op) = self.ops.get(pc) {
+            if steps >= step_limit {
+                return Err(CpuError::StepLimitExceeded { pc, steps });
+            }
+
+            let combo = |combo: Combo| match combo {
+                Combo::Value(v) => v,
+                Combo::A => a,
+                Combo::B => b,
+                Combo::C => c,
+            };
+
+            let divide = |operand: Combo| {
+                let shift = combo(operand);
+
+                if shift >= u64::BITS as u64 {
+                    return Err(CpuError::ShiftOverflow { pc, shift });
+                }
+
+                Ok(a >> shift)
+            };
+
+            match *op {
+                Op::Adv(operand) => a = divide(operand)?,
+                Op::Bxl(value) => b ^= value,
+                Op::Bst(operand) => b = combo(operand) & 0b111,
+                Op::Jnz(target) => {
+                    if a != 0 {
+                        pc = target;
+                        steps += 1;
+                        continue;
+                    }
+                }
+                Op::Bxc => b ^= c,
+                Op::Out(operand) => {
+                    if !out((combo(operand) & 0b111) as u8) {
+                        return Ok(());
+                    }
+                }
+                Op::Bdv(operand) => b = divide(operand)?,
+                Op::Cdv(operand) => c = divide(operand)?,
+                Op::Invalid(error) => return Err(self.errors[error].clone()),
+            }
+
+            pc += 2;
+            steps += 1;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cpu(program: &str) -> Cpu {
+        Cpu::parse(&format!(
+            "Register A: 0\nRegister B: 0\nRegister C: 0\n\nProgram: {}",
+            program
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_same_as_run() {
+        for program in [
+            "2,4,1,3,7,5,0,3,1,5,4,1,5,5,3,0",
+            "0,1,5,4,3,0",
+            // Jumps in the middle of an instruction
+            "2,4,5,5,0,1,3,3",
+            // Errors
+            "0,4,5,7,3,0",
+            "5,4,3",
+            "5,4,3,0",
+        ] {
+            let cpu = cpu(program);
+            let compiled = cpu.compile();
+
+            for a in [0, 1, 7, 64, 729, 117_440, 123_456_789] {
+                let mut interpreted = cpu.clone();
+                interpreted.set_registers(Registers { a, b: 0, c: 0 });
+                interpreted.set_step_limit(Some(1000));
+
+                assert_eq!(
+                    compiled.run(Registers { a, b: 0, c: 0 }, Some(1000)),
+                    interpreted.run(),
+                    "{} with A = {}",
+                    program,
+                    a
+                );
+            }
+        }
+    }
+}
@@ -1,188 +1,520 @@
-use crate::debug::debugln;
+use std::{collections::HashSet, fmt::Display};
+
+use lib::scan::ints;
+
+use crate::asm::{self, Opcode, OperandKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    A,
+    B,
+    C,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Registers {
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
+}
+
+impl Registers {
+    pub fn get(&self, register: Register) -> u64 {
+        match register {
+            Register::A => self.a,
+            Register::B => self.b,
+            Register::C => self.c,
+        }
+    }
+}
+
+impl Display for Registers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "A={} B={} C={}", self.a, self.b, self.c)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub pc: usize,
+    pub opcode: Opcode,
+    pub operand: u8,
+    // After the instruction was executed
+    pub registers: Registers,
+}
+
+impl Display for TraceEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let instruction = format!(
+            "{} {}",
+            self.opcode.mnemonic(),
+            asm::format_operand(self.opcode.operand_kind(), self.operand)
+        );
+
+        write!(f, "pc={:<3} {:<8} {}", self.pc, instruction, self.registers)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StopReason {
+    Halted,
+    Breakpoint(usize),
+    Watchpoint {
+        register: Register,
+        old: u64,
+        new: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CpuError {
+    InvalidOpcode { pc: usize, opcode: u8 },
+    InvalidComboOperand { pc: usize, operand: u8 },
+    // Last opcode of the program without its operand
+    MissingOperand { pc: usize },
+    // adv/bdv/cdv dividing by 2^shift with a shift too big for a u64
+    ShiftOverflow { pc: usize, shift: u64 },
+    StepLimitExceeded { pc: usize, steps: u64 },
+}
+
+impl Display for CpuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CpuError::InvalidOpcode { pc, opcode } => {
+                write!(f, "invalid opcode {} at {}", opcode, pc)
+            }
+            CpuError::InvalidComboOperand { pc, operand } => {
+                write!(f, "invalid combo operand {} at {}", operand, pc)
+            }
+            CpuError::MissingOperand { pc } => write!(f, "missing operand at {}", pc),
+            CpuError::ShiftOverflow { pc, shift } => {
+                write!(f, "division by 2^{} overflows at {}", shift, pc)
+            }
+            CpuError::StepLimitExceeded { pc, steps } => {
+                write!(f, "step limit exceeded after {} steps at {}", steps, pc)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CpuError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Checks every instruction can be decoded, execution can still fail on shift overflow or step
+// limit
+pub fn validate(program: &[u8]) -> Result<(), CpuError> {
+    for (i, instruction) in program.chunks(2).enumerate() {
+        let pc = i * 2;
+        let opcode = decode(program, pc)?;
+
+        if opcode.operand_kind() == OperandKind::Combo && instruction[1] > 6 {
+            return Err(CpuError::InvalidComboOperand {
+                pc,
+                operand: instruction[1],
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// Opcode at pc, which must be followed by an operand
+fn decode(program: &[u8], pc: usize) -> Result<Opcode, CpuError> {
+    let opcode =
+        Opcode::try_from(program[pc]).map_err(|opcode| CpuError::InvalidOpcode { pc, opcode })?;
+
+    if pc + 1 >= program.len() {
+        return Err(CpuError::MissingOperand { pc });
+    }
+
+    Ok(opcode)
+}
 
 #[derive(Clone)]
 pub struct Cpu {
     program: Vec<u8>,
 
-    pub reg_a: u64,
+    reg_a: u64,
     reg_b: u64,
     reg_c: u64,
 
     pc: usize,
+    stdout: Vec<u8>,
+
+    // Debugger
+    steps: u64,
+    step_limit: Option<u64>,
+    breakpoints: HashSet<usize>,
+    watchpoints: Vec<Register>,
+    trace: Option<Vec<TraceEntry>>,
 }
 
 impl Cpu {
-    pub fn from(input: &str) -> Self {
-        let (registers, program) = input.split_once("\n\n").unwrap();
-
-        let registers: Vec<u64> = registers
-            .lines()
-            .map(|l| l.trim().split_once(":").unwrap().1.trim())
-            .flat_map(|n| n.parse())
-            .collect();
-
-        let program = program
-            .trim()
-            .replace("Program: ", "")
-            .split(",")
-            .flat_map(|op| op.parse())
-            .collect();
-
-        Cpu {
+    // Registers A, B and C, a blank line, then the program. Values outside of 0..=255 can't be
+    // stored in the program, invalid opcodes only fail when decoded.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let error = |message: String| ParseError { message };
+
+        let (registers, program) = input
+            .split_once("\n\n")
+            .ok_or_else(|| error("Missing blank line before the program".to_string()))?;
+
+        let registers = ints(registers)
+            .map_err(|e| error(format!("Invalid registers: {}", e)))?
+            .into_iter()
+            .map(|n| u64::try_from(n).map_err(|_| error(format!("Invalid register value {}", n))))
+            .collect::<Result<Vec<u64>, ParseError>>()?;
+
+        let [reg_a, reg_b, reg_c] = registers[..] else {
+            return Err(error(format!(
+                "Expected 3 registers, got {}",
+                registers.len()
+            )));
+        };
+
+        let program = ints(program)
+            .map_err(|e| error(format!("Invalid program: {}", e)))?
+            .into_iter()
+            .map(|n| u8::try_from(n).map_err(|_| error(format!("Invalid program value {}", n))))
+            .collect::<Result<Vec<u8>, ParseError>>()?;
+
+        Ok(Cpu {
             program,
             pc: 0,
-            reg_a: registers[0],
-            reg_b: registers[1],
-            reg_c: registers[2],
-        }
+            reg_a,
+            reg_b,
+            reg_c,
+            stdout: Vec::new(),
+            steps: 0,
+            step_limit: None,
+            breakpoints: HashSet::new(),
+            watchpoints: Vec::new(),
+            trace: None,
+        })
     }
 
-    pub fn run(&mut self) -> Vec<u8> {
-        let mut stdout = Vec::new();
-        self.pc = 0;
-
-        while let Some(opcode) = self.program.get(self.pc) {
-            let operand = self.program.get(self.pc + 1).unwrap();
-
-            match opcode {
-                // ADV
-                0 => self.reg_a /= 2u64.pow(self.combo(*operand) as u32),
-                // BXL
-                1 => self.reg_b ^= *operand as u64,
-                // BST
-                2 => self.reg_b = self.combo(*operand) & 0b111,
-                // JNZ
-                3 => {
-                    if self.reg_a != 0 {
-                        self.pc = *operand as usize;
-                        continue;
-                    }
-                }
-                // BXC
-                4 => self.reg_b ^= self.reg_c,
-                // OUT
-                5 => stdout.push((self.combo(*operand) & 0b111) as u8),
-                // BDV
-                6 => self.reg_b = self.reg_a / 2u64.pow(self.combo(*operand) as u32),
-                // CDV
-                7 => self.reg_c = self.reg_a / 2u64.pow(self.combo(*operand) as u32),
-                _ => {
-                    panic!("Unsupported opcode")
-                }
-            }
+    // Runs the whole program from the start with the current registers, ignoring breakpoints
+    pub fn run(&mut self) -> Result<Vec<u8>, CpuError> {
+        self.restart();
 
-            self.pc += 2;
-        }
+        while self.step()?.is_some() {}
 
-        stdout
+        Ok(self.stdout.clone())
     }
 
-    pub fn reset(&mut self) {
-        self.reg_a = 0;
-        self.reg_b = 0;
-        self.reg_b = 0;
-        self.pc = 0;
+    pub fn validate(&self) -> Result<(), CpuError> {
+        validate(&self.program)
     }
 
-    pub fn find_program_as_stdout(&mut self) -> u64 {
-        let program: Vec<u8> = self.program.iter().rev().copied().collect();
+    // Executes the instruction at `pc`, None if the program halted
+    pub fn step(&mut self) -> Result<Option<TraceEntry>, CpuError> {
+        if self.is_halted() {
+            return Ok(None);
+        }
+
+        let pc = self.pc;
+
+        if self.step_limit.is_some_and(|limit| self.steps >= limit) {
+            return Err(CpuError::StepLimitExceeded {
+                pc,
+                steps: self.steps,
+            });
+        }
 
-        debugln!("Value to find: {:?}", program);
+        let opcode = decode(&self.program, pc)?;
+        let operand = self.program[pc + 1];
 
-        let r = self.find_factors(&program, &[]).unwrap();
+        self.execute(opcode, operand)?;
+        self.steps += 1;
 
-        debugln!("{:?}", r);
+        let entry = TraceEntry {
+            pc,
+            opcode,
+            operand,
+            registers: self.registers(),
+        };
 
-        self.a_from_factors(&r)
+        if let Some(trace) = &mut self.trace {
+            trace.push(entry.clone());
+        }
+
+        Ok(Some(entry))
     }
 
-    fn find_factors(&mut self, values: &[u8], previous: &[u32]) -> Option<Vec<u32>> {
-        if values.is_empty() {
-            return Some(previous.to_vec());
+    fn execute(&mut self, opcode: Opcode, operand: u8) -> Result<(), CpuError> {
+        match opcode {
+            Opcode::Adv => self.reg_a = self.divide(operand)?,
+            Opcode::Bxl => self.reg_b ^= operand as u64,
+            Opcode::Bst => self.reg_b = self.combo(operand)? & 0b111,
+            Opcode::Jnz => {
+                if self.reg_a != 0 {
+                    self.pc = operand as usize;
+                    return Ok(());
+                }
+            }
+            Opcode::Bxc => self.reg_b ^= self.reg_c,
+            Opcode::Out => {
+                let value = self.combo(operand)? & 0b111;
+                self.stdout.push(value as u8);
+            }
+            Opcode::Bdv => self.reg_b = self.divide(operand)?,
+            Opcode::Cdv => self.reg_c = self.divide(operand)?,
         }
 
-        let value = values[0];
-        let remaining_values = &values[1..values.len()];
+        self.pc += 2;
 
-        debugln!("{}Previous: {:?}", "\t".repeat(previous.len()), previous);
+        Ok(())
+    }
 
-        debugln!(
-            "{}Searching value: {} (remaining: {:?})",
-            "\t".repeat(previous.len()),
-            value,
-            remaining_values
-        );
-        let range = self.range_for_factors(previous);
+    // A / 2^combo
+    fn divide(&self, operand: u8) -> Result<u64, CpuError> {
+        let shift = self.combo(operand)?;
 
-        debugln!("{}For range: {:?}", "\t".repeat(previous.len()), range);
+        if shift >= u64::BITS as u64 {
+            return Err(CpuError::ShiftOverflow { pc: self.pc, shift });
+        }
 
-        for a in range {
-            if value == self.run_for_a(a as u64)[0] {
-                let mut factor = (a % 8) as u32;
+        Ok(self.reg_a >> shift)
+    }
 
-                // We need to discard the first pass [0] (as it doesn't translate in other ranges)
-                if previous.is_empty() {
-                    factor -= 1
-                }
+    // Runs from the current state until the program halts, reaches a breakpoint (other than
+    // the current instruction) or changes a watched register
+    pub fn resume(&mut self) -> Result<StopReason, CpuError> {
+        let start_pc = self.pc;
+        let start_steps = self.steps;
 
-                debugln!("Found {} for A = {} (factor: {})", value, a, factor);
+        loop {
+            if self.is_halted() {
+                return Ok(StopReason::Halted);
+            }
 
-                let mut previous = previous.to_vec();
-                previous.insert(0, factor);
+            if (self.steps != start_steps || self.pc != start_pc)
+                && self.breakpoints.contains(&self.pc)
+            {
+                return Ok(StopReason::Breakpoint(self.pc));
+            }
 
-                if let Some(factors) = self.find_factors(remaining_values, &previous) {
-                    return Some(factors);
-                }
+            let before = self.registers();
+            self.step()?;
+            let after = self.registers();
+
+            if let Some(register) = self
+                .watchpoints
+                .iter()
+                .find(|r| before.get(**r) != after.get(**r))
+            {
+                return Ok(StopReason::Watchpoint {
+                    register: *register,
+                    old: before.get(*register),
+                    new: after.get(*register),
+                });
             }
         }
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.pc >= self.program.len()
+    }
+
+    pub fn set_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.insert(pc);
+    }
 
-        debugln!("{}<- Nope", "\t".repeat(previous.len() + 1));
-        None
+    pub fn clear_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.remove(&pc);
     }
 
-    fn range_for_factors(&self, factors: &[u32]) -> std::ops::Range<usize> {
-        if factors.is_empty() {
-            return 1..8;
+    pub fn watch(&mut self, register: Register) {
+        if !self.watchpoints.contains(&register) {
+            self.watchpoints.push(register);
         }
+    }
 
-        let start = 8usize.pow(factors.len() as u32);
+    pub fn unwatch(&mut self, register: Register) {
+        self.watchpoints.retain(|r| *r != register);
+    }
 
-        let delta: usize = factors
-            .iter()
-            .enumerate()
-            .map(|(i, f)| (*f as usize) * 8usize.pow(1 + i as u32))
-            .sum();
+    // Total number of instructions which may be executed since the last reset
+    pub fn set_step_limit(&mut self, limit: Option<u64>) {
+        self.step_limit = limit;
+    }
 
-        debugln!("[range] ({}, {})", start, delta);
+    pub fn enable_trace(&mut self) {
+        self.trace.get_or_insert_with(Vec::new);
+    }
+
+    pub fn trace(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or(&[])
+    }
+
+    pub fn registers(&self) -> Registers {
+        Registers {
+            a: self.reg_a,
+            b: self.reg_b,
+            c: self.reg_c,
+        }
+    }
+
+    // Keeps the execution state, use `reset` first to restart the program
+    pub fn set_registers(&mut self, registers: Registers) {
+        self.reg_a = registers.a;
+        self.reg_b = registers.b;
+        self.reg_c = registers.c;
+    }
+
+    pub fn program(&self) -> &[u8] {
+        &self.program
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn stdout(&self) -> &[u8] {
+        &self.stdout
+    }
 
-        (start + delta)..(start + delta + 8)
+    pub fn disassemble(&self) -> String {
+        asm::disassemble(&self.program)
     }
 
-    fn run_for_a(&mut self, a: u64) -> Vec<u8> {
-        self.reset();
-        self.reg_a = a;
-        self.run()
+    // Clears registers and execution state, breakpoints and watchpoints are kept
+    pub fn reset(&mut self) {
+        self.set_registers(Registers::default());
+        self.restart();
     }
 
-    fn a_from_factors(&self, factors: &[u32]) -> u64 {
-        let delta: u64 = factors
-            .iter()
-            .enumerate()
-            .map(|(i, n)| *n as u64 * 8u64.pow(i as u32))
-            .sum();
+    // Clears the execution state only
+    fn restart(&mut self) {
+        self.pc = 0;
+        self.stdout.clear();
+        self.steps = 0;
 
-        8u64.pow(factors.len() as u32 - 1) + delta
+        if let Some(trace) = &mut self.trace {
+            trace.clear();
+        }
     }
 
-    fn combo(&self, operand: u8) -> u64 {
+    fn combo(&self, operand: u8) -> Result<u64, CpuError> {
         match operand {
-            0..=3 => operand as u64,
-            4 => self.reg_a,
-            5 => self.reg_b,
-            6 => self.reg_c,
-            _ => {
-                panic!("Unsupported combo operand");
-            }
+            0..=3 => Ok(operand as u64),
+            4 => Ok(self.reg_a),
+            5 => Ok(self.reg_b),
+            6 => Ok(self.reg_c),
+            _ => Err(CpuError::InvalidComboOperand {
+                pc: self.pc,
+                operand,
+            }),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cpu(a: u64, program: &str) -> Cpu {
+        Cpu::parse(&format!(
+            "Register A: {}\nRegister B: 0\nRegister C: 0\n\nProgram: {}",
+            a, program
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            cpu(1, "5,4,8,0").run(),
+            Err(CpuError::InvalidOpcode { pc: 2, opcode: 8 })
+        );
+        assert_eq!(
+            cpu(1, "5,4,5,7").run(),
+            Err(CpuError::InvalidComboOperand { pc: 2, operand: 7 })
+        );
+        assert_eq!(
+            cpu(1, "5,4,5").run(),
+            Err(CpuError::MissingOperand { pc: 2 })
+        );
+        assert_eq!(
+            cpu(64, "0,4").run(),
+            Err(CpuError::ShiftOverflow { pc: 0, shift: 64 })
+        );
+
+        let mut looping = cpu(1, "5,4,3,0");
+        looping.set_step_limit(Some(10));
+
+        assert_eq!(
+            looping.run(),
+            Err(CpuError::StepLimitExceeded { pc: 0, steps: 10 })
+        );
+    }
+
+    #[test]
+    fn test_run_twice() {
+        let mut looping = cpu(3, "0,1,5,4,3,0");
+        looping.set_step_limit(Some(10));
+        looping.enable_trace();
+
+        assert_eq!(looping.run(), Ok(vec![1, 0]));
+        assert_eq!(looping.steps(), 6);
+
+        looping.set_registers(Registers { a: 3, b: 0, c: 0 });
+
+        assert_eq!(looping.run(), Ok(vec![1, 0]));
+        assert_eq!(looping.steps(), 6);
+        assert_eq!(looping.trace().len(), 6);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(validate(&[2, 4, 1, 3, 7, 5, 0, 3, 3, 0]), Ok(()));
+        // Literal operand 7 is fine
+        assert_eq!(validate(&[1, 7, 3, 0]), Ok(()));
+        assert_eq!(
+            validate(&[1, 7, 6, 7]),
+            Err(CpuError::InvalidComboOperand { pc: 2, operand: 7 })
+        );
+        assert_eq!(
+            validate(&[1, 7, 3]),
+            Err(CpuError::MissingOperand { pc: 2 })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let message = |input: &str| Cpu::parse(input).err().map(|error| error.message);
+
+        assert_eq!(
+            message("Register A: -1\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1"),
+            Some("Invalid register value -1".to_string())
+        );
+        assert_eq!(
+            message("Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 0,256"),
+            Some("Invalid program value 256".to_string())
+        );
+        assert_eq!(
+            message("Register A: 1\nRegister B: 0\n\nProgram: 0,1"),
+            Some("Expected 3 registers, got 2".to_string())
+        );
+        assert_eq!(
+            message("Register A: 1\nRegister B: 0\nRegister C: 0\nProgram: 0,1"),
+            Some("Missing blank line before the program".to_string())
+        );
+    }
+}
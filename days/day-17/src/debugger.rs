@@ -0,0 +1,213 @@
+use std::io::{BufRead, Write};
+
+use crate::cpu::{Cpu, Register, Registers, StopReason};
+
+const HELP: &str = "\
+s [n]          step n instructions (default 1)
+c              continue until halt, breakpoint or watchpoint
+b <pc>         set breakpoint       d <pc>   delete breakpoint
+w <a|b|c>      watch register       u <a|b|c> unwatch register
+limit <n|off>  instruction budget for `c`
+r              registers            o        output
+t              trace                l        listing
+reset <a>      restart with register A = a
+q              quit";
+
+// Line based debugger session, e.g. `cargo run -- --debug` then `b 6`, `c`, `s`, `r`
+pub fn repl(cpu: &mut Cpu, input: impl BufRead, output: &mut impl Write) -> std::io::Result<()> {
+    cpu.enable_trace();
+
+    writeln!(output, "{}", HELP)?;
+
+    if let Err(error) = cpu.validate() {
+        writeln!(output, "Warning: {}", error)?;
+    }
+
+    write!(output, "> ")?;
+    output.flush()?;
+
+    for line in input.lines() {
+        let line = line?;
+        let parts: Vec<&str> = line.split_whitespace().collect();
+
+        match parts.as_slice() {
+            ["q"] => break,
+            [] => {}
+            _ => writeln!(output, "{}", command(cpu, &parts))?,
+        }
+
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+fn command(cpu: &mut Cpu, parts: &[&str]) -> String {
+    match parts {
+        ["s"] => step(cpu, 1),
+        ["s", n] => match n.parse() {
+            Ok(n) => step(cpu, n),
+            Err(_) => format!("Invalid count {:?}", n),
+        },
+        ["c"] => match cpu.resume() {
+            Ok(StopReason::Halted) => format!("Halted, output: {:?}", cpu.stdout()),
+            Ok(StopReason::Breakpoint(pc)) => format!("Breakpoint at {}", pc),
+            Ok(StopReason::Watchpoint { register, old, new }) => {
+                format!(
+                    "{:?} changed {} -> {} (pc = {})",
+                    register,
+                    old,
+                    new,
+                    cpu.pc()
+                )
+            }
+            Err(error) => format!("Error: {}", error),
+        },
+        ["b", pc] => match pc.parse() {
+            Ok(pc) => {
+                cpu.set_breakpoint(pc);
+                format!("Breakpoint set at {}", pc)
+            }
+            Err(_) => format!("Invalid address {:?}", pc),
+        },
+        ["d", pc] => match pc.parse() {
+            Ok(pc) => {
+                cpu.clear_breakpoint(pc);
+                format!("Breakpoint removed at {}", pc)
+            }
+            Err(_) => format!("Invalid address {:?}", pc),
+        },
+        ["w", register] => match parse_register(register) {
+            Some(register) => {
+                cpu.watch(register);
+                format!("Watching {:?}", register)
+            }
+            None => format!("Invalid register {:?}", register),
+        },
+        ["u", register] => match parse_register(register) {
+            Some(register) => {
+                cpu.unwatch(register);
+                format!("Not watching {:?}", register)
+            }
+            None => format!("Invalid register {:?}", register),
+        },
+        ["limit", "off"] => {
+            cpu.set_step_limit(None);
+            "No instruction budget".to_string()
+        }
+        ["limit", n] => match n.parse() {
+            Ok(n) => {
+                cpu.set_step_limit(Some(n));
+                format!("Instruction budget: {}", n)
+            }
+            Err(_) => format!("Invalid budget {:?}", n),
+        },
+        ["r"] => format!("pc={} steps={} {}", cpu.pc(), cpu.steps(), cpu.registers()),
+        ["o"] => format!("{:?}", cpu.stdout()),
+        ["t"] => cpu
+            .trace()
+            .iter()
+            .map(|entry| entry.to_string())
+            .collect::<Vec<String>>()
+            .join("\n"),
+        ["l"] => cpu
+            .disassemble()
+            .lines()
+            .enumerate()
+            .map(|(i, line)| format!("{:>3} {}", i * 2, line))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        ["reset", a] => match a.parse() {
+            Ok(a) => {
+                cpu.reset();
+                cpu.set_registers(Registers {
+                    a,
+                    ..Registers::default()
+                });
+                format!("Restarted with {}", cpu.registers())
+            }
+            Err(_) => format!("Invalid value {:?}", a),
+        },
+        _ => format!("Unknown command {:?}\n{}", parts.join(" "), HELP),
+    }
+}
+
+fn step(cpu: &mut Cpu, n: usize) -> String {
+    let mut lines = vec![];
+
+    for _ in 0..n {
+        match cpu.step() {
+            Ok(Some(entry)) => lines.push(entry.to_string()),
+            Ok(None) => {
+                lines.push("Halted".to_string());
+                break;
+            }
+            Err(error) => {
+                lines.push(format!("Error: {}", error));
+                break;
+            }
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn parse_register(register: &str) -> Option<Register> {
+    match register.to_lowercase().as_str() {
+        "a" => Some(Register::A),
+        "b" => Some(Register::B),
+        "c" => Some(Register::C),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repl() {
+        let mut cpu =
+            Cpu::parse("Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0")
+                .unwrap();
+        let mut output = Vec::new();
+
+        repl(&mut cpu, "b 4\nc\ns\nr\nq\n".as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("> Breakpoint at 4\n"));
+        assert!(output.contains("> pc=4   jnz 0 "));
+        assert!(output.contains("> pc=0 steps=3 A=364 B=0 C=0\n"));
+    }
+
+    #[test]
+    fn test_step_limit() {
+        // Never halts as A is never divided
+        let mut cpu =
+            Cpu::parse("Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 5,4,3,0").unwrap();
+        let mut output = Vec::new();
+
+        repl(&mut cpu, "limit 10\nc\n".as_bytes(), &mut output).unwrap();
+
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("> Error: step limit exceeded after 10 steps at 0\n"));
+        assert_eq!(cpu.stdout().len(), 5);
+    }
+
+    #[test]
+    fn test_invalid_listing() {
+        let mut cpu =
+            Cpu::parse("Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 5,4,8,0").unwrap();
+        let mut output = Vec::new();
+
+        repl(&mut cpu, "l\nq\n".as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("Warning: "));
+        assert!(output.contains(">   0 out a\n  2 .byte 8 0\n"));
+    }
+}
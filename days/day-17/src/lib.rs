@@ -0,0 +1,7 @@
+// Shared by the binary and the benchmark
+pub mod asm;
+pub mod compiled;
+pub mod cpu;
+mod debug;
+pub mod debugger;
+pub mod solver;
@@ -1,16 +1,51 @@
-use std::{fs, time::Instant};
+use std::{fs, io, time::Instant};
 
-use cpu::Cpu;
+use day_17::{asm, cpu::Cpu, debugger, solver};
 
 type ParsedInput = Cpu;
 type Output = String;
 
-mod cpu;
-mod debug;
-
 fn main() {
     let input = input();
 
+    match std::env::args().nth(1).as_deref() {
+        Some("--disassemble") => return println!("{}", input.disassemble()),
+        Some("--debug") => {
+            let mut cpu = input.clone();
+            return debugger::repl(&mut cpu, io::stdin().lock(), &mut io::stdout()).unwrap();
+        }
+        // Reads the source from stdin, e.g. `cargo run -- --disassemble | cargo run -- --assemble`
+        Some("--assemble") => {
+            match asm::assemble(&io::read_to_string(io::stdin()).unwrap()) {
+                Ok(program) => println!("Program: {}", format_output(&program)),
+                Err(error) => eprintln!("Error: {}", error),
+            }
+
+            return;
+        }
+        // Smallest A making the program output the given values, e.g. `--find 2,4,1 [MAX_TRIES]`
+        Some("--find") => {
+            let mut args = std::env::args().skip(2);
+            let target: Vec<u8> = args
+                .next()
+                .expect("Missing output")
+                .split(',')
+                .map(|n| n.parse().expect("Invalid output"))
+                .collect();
+            let max_tries = args.next().map_or(solver::DEFAULT_MAX_TRIES, |n| {
+                n.parse().expect("Invalid number of tries")
+            });
+
+            match input.find_a_for_output_within(&target, max_tries) {
+                Ok(a) => println!("A = {}", a),
+                Err(error) => eprintln!("Error: {:?}", error),
+            }
+
+            return;
+        }
+        _ => {}
+    }
+
     let start = Instant::now();
     println!("Result (part 1): {} [{:?}]", part1(&input), start.elapsed());
 
@@ -24,27 +59,27 @@ fn input() -> ParsedInput {
 }
 
 fn parse_input(input: &str) -> ParsedInput {
-    Cpu::from(input)
+    Cpu::parse(input).unwrap_or_else(|error| panic!("Invalid input, {}", error))
 }
 
 fn part1(input: &ParsedInput) -> Output {
     let mut cpu = input.clone();
 
-    let stdout = cpu.run();
+    format_output(&cpu.run().expect("Invalid program"))
+}
+
+fn part2(input: &ParsedInput) -> usize {
+    input.find_quine().unwrap() as usize
+}
 
-    stdout
+fn format_output(values: &[u8]) -> String {
+    values
         .iter()
         .map(|n| n.to_string())
         .collect::<Vec<String>>()
         .join(",")
 }
 
-fn part2(input: &ParsedInput) -> usize {
-    let mut cpu = input.clone();
-
-    cpu.find_program_as_stdout() as usize
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,364 @@
+use crate::{
+    asm::{Opcode, OperandKind},
+    compiled::CompiledProgram,
+    cpu::{Cpu, CpuError, Registers},
+    debug::debugln,
+};
+
+// Values of A tried by default when the program can't be split into independent iterations
+pub const DEFAULT_MAX_TRIES: u64 = 1 << 22;
+// Instructions allowed per run, programs that don't halt within it are treated as not matching
+const STEP_BUDGET: u64 = 100_000;
+// Widest A chunk we are willing to enumerate per loop iteration
+const MAX_CHUNK_BITS: u32 = 16;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SearchError {
+    // Proven: no value of A produces the target
+    NoSolution,
+    // Brute force found nothing below this bound, larger values weren't tried
+    NotFoundBelow(u64),
+    InvalidProgram(CpuError),
+}
+
+// Program that loops over A by a fixed shift: one iteration per `shift` bits of A, each one
+// writing `outputs` values. Unless B or C are carried from an iteration to the next, those values
+// only depend on A's remaining bits.
+#[derive(Debug, PartialEq)]
+struct LoopShape {
+    shift: u32,
+    outputs: usize,
+    carries: bool,
+}
+
+impl Cpu {
+    // Smallest A making the program output itself
+    pub fn find_quine(&self) -> Result<u64, SearchError> {
+        self.find_a_for_output(self.program())
+    }
+
+    // Smallest A making the program output `target`, B and C keep their initial values
+    pub fn find_a_for_output(&self, target: &[u8]) -> Result<u64, SearchError> {
+        self.find_a_for_output_within(target, DEFAULT_MAX_TRIES)
+    }
+
+    // Same, brute forcing at most `max_tries` values of A when the iterations aren't independent
+    pub fn find_a_for_output_within(
+        &self,
+        target: &[u8],
+        max_tries: u64,
+    ) -> Result<u64, SearchError> {
+        self.validate().map_err(SearchError::InvalidProgram)?;
+
+        let program = self.compile();
+
+        let a = match analyse(self.program()) {
+            Some(shape) if !shape.carries => {
+                debugln!("Program loops over A: {:?}", shape);
+
+                search_chunks(&program, target, &shape)
+            }
+            Some(shape) => {
+                debugln!("Program loops over A carrying B or C: {:?}", shape);
+
+                let (low, high) = a_range(target, &shape)?;
+                brute_force(
+                    &program,
+                    target,
+                    low,
+                    high.min(low.saturating_add(max_tries)),
+                )
+            }
+            None => {
+                debugln!("Program can't be analysed, brute forcing A");
+
+                brute_force(&program, target, 0, max_tries)
+            }
+        }?;
+
+        debug_assert_eq!(program.run_for_a(a).as_deref(), Ok(target));
+
+        Ok(a)
+    }
+}
+
+// The last outputs only depend on the high bits of A, so A is built one chunk at a time from
+// the top, checking that the output matches the end of the target. Trying chunks in increasing
+// order makes the first full match the smallest A.
+fn search_chunks(
+    program: &CompiledProgram,
+    target: &[u8],
+    shape: &LoopShape,
+) -> Result<u64, SearchError> {
+    if target.is_empty() && shape.outputs == 0 {
+        return Ok(0);
+    }
+
+    let iterations = iterations(target, shape)?;
+
+    if iterations as u32 * shape.shift > u64::BITS {
+        return Err(SearchError::NoSolution);
+    }
+
+    search_chunk(program, target, shape, iterations, 0).ok_or(SearchError::NoSolution)
+}
+
+// Number of loop iterations writing `target`
+fn iterations(target: &[u8], shape: &LoopShape) -> Result<usize, SearchError> {
+    if shape.outputs == 0 || !target.len().is_multiple_of(shape.outputs) {
+        return Err(SearchError::NoSolution);
+    }
+
+    match target.len() / shape.outputs {
+        0 => Err(SearchError::NoSolution),
+        iterations => Ok(iterations),
+    }
+}
+
+// Values of A (end exclusive, saturating at u64::MAX) running exactly the number of iterations
+// writing `target`: the loop stops once all the bits of A are shifted out
+fn a_range(target: &[u8], shape: &LoopShape) -> Result<(u64, u64), SearchError> {
+    if target.is_empty() && shape.outputs == 0 {
+        return Ok((0, 1));
+    }
+
+    let iterations = iterations(target, shape)? as u64;
+    let shifted = |n: u64| {
+        n.checked_mul(shape.shift as u64)
+            .filter(|bits| *bits < u64::BITS as u64)
+            .map(|bits| 1 << bits)
+    };
+
+    let low = match iterations {
+        1 => 0,
+        n => shifted(n - 1).ok_or(SearchError::NoSolution)?,
+    };
+
+    Ok((low, shifted(iterations).unwrap_or(u64::MAX)))
+}
+
+fn search_chunk(
+    program: &CompiledProgram,
+    target: &[u8],
+    shape: &LoopShape,
+    remaining: usize,
+    high_bits: u64,
+) -> Option<u64> {
+    if remaining == 0 {
+        return Some(high_bits);
+    }
+
+    let expected = &target[(remaining - 1) * shape.outputs..];
+
+    for chunk in 0..(1 << shape.shift) {
+        let a = (high_bits << shape.shift) | chunk;
+
+        if output_for(program, a, expected).is_some_and(|out| out == expected) {
+            debugln!("{}A = {} -> {:?}", "\t".repeat(remaining), a, expected);
+
+            if let Some(a) = search_chunk(program, target, shape, remaining - 1, a) {
+                return Some(a);
+            }
+        }
+    }
+
+    None
+}
+
+// Tries A in `low..high`, values below `low` are known not to match
+fn brute_force(
+    program: &CompiledProgram,
+    target: &[u8],
+    low: u64,
+    high: u64,
+) -> Result<u64, SearchError> {
+    (low..high)
+        .find(|a| output_for(program, *a, target).is_some_and(|out| out == target))
+        .ok_or(SearchError::NotFoundBelow(high))
+}
+
+// Output of the program for A, stopping early once it diverges from `target`. None if the
+// program fails, e.g. by not halting within the step budget.
+fn output_for(program: &CompiledProgram, a: u64, target: &[u8]) -> Option<Vec<u8>> {
+    let registers = Registers {
+        a,
+        ..program.registers()
+    };
+
+    program
+        .run_matching(registers, target, Some(STEP_BUDGET))
+        .ok()
+}
+
+// Checks the program is a single loop (`jnz 0` as its last instruction and only jump) shifting
+// A by constants only. Iterations are independent when B and C are always written before being
+// read in the loop.
+fn analyse(program: &[u8]) -> Option<LoopShape> {
+    if program.len() < 2
+        || !program.len().is_multiple_of(2)
+        || program[program.len() - 2..] != [3, 0]
+    {
+        return None;
+    }
+
+    let mut shift = 0;
+    let mut outputs = 0;
+    let (mut b_written, mut c_written) = (false, false);
+    let mut carries = false;
+
+    for instruction in program[..program.len() - 2].chunks(2) {
+        let opcode = Opcode::try_from(instruction[0]).ok()?;
+        let operand = instruction[1];
+
+        if opcode.operand_kind() == OperandKind::Combo {
+            match operand {
+                5 if !b_written => carries = true,
+                6 if !c_written => carries = true,
+                7 => return None,
+                _ => {}
+            }
+        }
+
+        match opcode {
+            Opcode::Adv if operand <= 3 => shift += operand as u32,
+            Opcode::Adv | Opcode::Jnz => return None,
+            Opcode::Bxl if !b_written => carries = true,
+            Opcode::Bxc if !b_written || !c_written => carries = true,
+            Opcode::Bst | Opcode::Bdv => b_written = true,
+            Opcode::Cdv => c_written = true,
+            Opcode::Out | Opcode::Bxl | Opcode::Bxc => {}
+        }
+
+        if opcode == Opcode::Out {
+            outputs += 1;
+        }
+    }
+
+    if shift == 0 || (!carries && shift > MAX_CHUNK_BITS) {
+        return None;
+    }
+
+    Some(LoopShape {
+        shift,
+        outputs,
+        carries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cpu(program: &str) -> Cpu {
+        Cpu::parse(&format!(
+            "Register A: 0\nRegister B: 0\nRegister C: 0\n\nProgram: {}",
+            program
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_analyse() {
+        assert_eq!(
+            analyse(&[2, 4, 1, 3, 7, 5, 0, 3, 1, 5, 4, 1, 5, 5, 3, 0]),
+            Some(LoopShape {
+                shift: 3,
+                outputs: 1,
+                carries: false
+            })
+        );
+        // B read before being written
+        assert_eq!(
+            analyse(&[5, 5, 0, 3, 3, 0]),
+            Some(LoopShape {
+                shift: 3,
+                outputs: 1,
+                carries: true
+            })
+        );
+        // Shift depends on a register
+        assert_eq!(analyse(&[2, 4, 5, 5, 0, 5, 3, 0]), None);
+    }
+
+    #[test]
+    fn test_find_a_for_output() {
+        let cpu = cpu("2,4,1,3,7,5,0,3,1,5,4,1,5,5,3,0");
+
+        let mut check = cpu.clone();
+        check.set_registers(Registers {
+            a: 123_456_789,
+            ..check.registers()
+        });
+        let target = check.run().unwrap();
+
+        let a = cpu.find_a_for_output(&target).unwrap();
+        check.set_registers(Registers {
+            a,
+            ..Registers::default()
+        });
+
+        assert!(a <= 123_456_789);
+        assert_eq!(check.run(), Ok(target.clone()));
+        assert!((0..a.min(100_000))
+            .all(|smaller| output_for(&cpu.compile(), smaller, &target) != Some(target.clone())));
+
+        // Outputs A % 8 then shifts: the last output can't be 0 (the loop would have stopped)
+        assert_eq!(
+            self::cpu("2,4,5,5,0,3,3,0").find_a_for_output(&[1, 0]),
+            Err(SearchError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn test_carried_registers() {
+        // B is toggled every iteration: 9 outputs need 9 iterations, so A >= 2^24
+        let cpu = cpu("0,3,1,1,5,5,3,0");
+
+        assert_eq!(
+            cpu.find_a_for_output(&[1, 0, 1, 0, 1, 0, 1, 0, 1]),
+            Ok(1 << 24)
+        );
+        assert_eq!(
+            cpu.find_a_for_output_within(&[1, 0, 1, 0, 1, 0, 1, 0, 0], 1000),
+            Err(SearchError::NotFoundBelow((1 << 24) + 1000))
+        );
+        assert_eq!(
+            cpu.find_a_for_output_within(&[1, 0, 1, 1], 1 << 12),
+            Err(SearchError::NotFoundBelow(1 << 12))
+        );
+        assert_eq!(cpu.find_a_for_output(&[1]), Ok(0));
+    }
+
+    #[test]
+    fn test_empty_target() {
+        // No output at all
+        assert_eq!(cpu("0,3,3,0").find_a_for_output(&[]), Ok(0));
+        assert_eq!(
+            cpu("0,3,3,0").find_a_for_output(&[0]),
+            Err(SearchError::NoSolution)
+        );
+        assert_eq!(
+            cpu("0,3,5,4,3,0").find_a_for_output(&[]),
+            Err(SearchError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn test_brute_force() {
+        // Shifts A by its own low bits
+        let cpu = cpu("2,4,5,5,0,5,3,0");
+
+        assert_eq!(cpu.find_a_for_output(&[2, 2]), Ok(10));
+        assert_eq!(
+            cpu.find_a_for_output_within(&[7, 7, 7, 7, 7, 7, 7], 1000),
+            Err(SearchError::NotFoundBelow(1000))
+        );
+        assert_eq!(
+            self::cpu("2,7,5,4,3,0").find_quine(),
+            Err(SearchError::InvalidProgram(CpuError::InvalidComboOperand {
+                pc: 0,
+                operand: 7
+            }))
+        );
+    }
+}
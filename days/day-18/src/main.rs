@@ -1,11 +1,11 @@
 use std::{
     cmp::Reverse,
-    collections::{BinaryHeap, HashMap, HashSet},
+    collections::{BinaryHeap, HashSet},
     fs,
     time::Instant,
 };
 
-use lib::{debugln, grid, Grid2d, Point};
+use lib::{debugln, Grid2d, Point, PointMap, PointSet};
 
 type ParsedInput = Vec<Point>;
 type Output = u64;
@@ -44,26 +44,25 @@ fn find_shortest_path(
     size: (usize, usize),
     obstacles: &HashSet<Point>,
 ) -> Option<Vec<Point>> {
-    let mut visited: HashSet<Point> = HashSet::new();
+    let mut visited = PointSet::new(size);
     let mut to_visit: BinaryHeap<Reverse<(u64, Point)>> = BinaryHeap::new();
-    let mut distances: HashMap<Point, u64> = HashMap::new();
-    let mut reverse_path: HashMap<Point, Point> = HashMap::new();
+    let mut distances: PointMap<u64> = PointMap::new(size);
+    let mut reverse_path: PointMap<Point> = PointMap::new(size);
 
     let grid = Grid2d::new(vec![vec![(); size.1]; size.0]);
 
     to_visit.push(Reverse((0, start)));
-    distances.insert(start, 0);
+    // A start outside of the memory space has no path
+    distances.insert(start, 0).ok()?;
 
     while let Some(Reverse((_, pos))) = to_visit.pop() {
-        if visited.contains(&pos) {
+        if !visited.insert(pos).ok()? {
             continue;
         }
 
-        visited.insert(pos);
-
         let current_distance = *distances.get(&pos).unwrap_or(&u64::MAX);
 
-        for (_, neighboor_pos, neighboor_dir) in grid
+        for (_, neighboor_pos, _) in grid
             .cardinal_neighboors(pos)
             .filter(|(_, n_pos, _)| !obstacles.contains(n_pos))
         {
@@ -71,8 +70,10 @@ fn find_shortest_path(
             let new_neighboor_distance = current_distance.saturating_add(1);
 
             if new_neighboor_distance <= existing_neighboor_distance {
-                *distances.entry(neighboor_pos).or_insert(u64::MAX) = new_neighboor_distance;
-                reverse_path.insert(neighboor_pos, pos);
+                distances
+                    .insert(neighboor_pos, new_neighboor_distance)
+                    .ok()?;
+                reverse_path.insert(neighboor_pos, pos).ok()?;
 
                 to_visit.push(Reverse((new_neighboor_distance, neighboor_pos)));
             }
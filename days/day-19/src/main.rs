@@ -1,10 +1,6 @@
-use std::{
-    collections::{BTreeSet, HashMap},
-    fs,
-    time::Instant,
-};
+use std::{collections::BTreeSet, fs, time::Instant};
 
-use lib::debugln;
+use lib::{debugln, memo::Memo};
 
 #[derive(Eq, PartialEq, Clone)]
 struct Towel {
@@ -74,7 +70,7 @@ fn parse_input(input: &str) -> ParsedInput {
 
 fn part1(input: &ParsedInput) -> Output {
     let mut r = 0;
-    let mut cache: HashMap<String, u64> = HashMap::new();
+    let mut cache: Memo<String, u64> = Memo::new();
     for (i, pattern) in input.patterns.iter().enumerate() {
         debugln!("[{}/{}] Pattern: {}", i + 1, input.patterns.len(), pattern);
         if find_pattern(pattern, &input.towels, &mut cache) != 0 {
@@ -83,12 +79,14 @@ fn part1(input: &ParsedInput) -> Output {
         }
     }
 
+    debugln!("{:?}", cache.stats());
+
     r
 }
 
 fn part2(input: &ParsedInput) -> Output {
     let mut r = 0;
-    let mut cache: HashMap<String, u64> = HashMap::new();
+    let mut cache: Memo<String, u64> = Memo::new();
     for (i, pattern) in input.patterns.iter().enumerate() {
         debugln!("[{}/{}] Pattern: {}", i + 1, input.patterns.len(), pattern);
         r += find_pattern(pattern, &input.towels, &mut cache);
@@ -97,45 +95,25 @@ fn part2(input: &ParsedInput) -> Output {
     r
 }
 
-fn find_pattern(pattern: &str, towels: &BTreeSet<Towel>, cache: &mut HashMap<String, u64>) -> u64 {
+fn find_pattern(pattern: &str, towels: &BTreeSet<Towel>, cache: &mut Memo<String, u64>) -> u64 {
     if pattern.is_empty() {
         debugln!("<-");
         return 1;
     }
 
-    if cache.contains_key(pattern) {
-        // debugln!("Cache hit");
-        debugln!(
-            "{: >20} Cache match: {} ({:?})",
-            "",
-            pattern,
-            cache[pattern]
-        );
-        return cache[pattern];
-    }
-
-    debugln!("{: >20}", pattern);
-
-    let mut result = 0;
+    cache.get_or_compute(pattern, &|cache, pattern: &str| {
+        debugln!("{: >20}", pattern);
 
-    for towel in towels.iter() {
-        // debugln!("[{}] Checking towel {}", pattern, towel.stripes);
-        // debugln!("{: >20} Checking: {}", "", towel.stripes);
-        if pattern.starts_with(&towel.stripes) {
-            // debugln!("\tMatch {} with {}", pattern, towel.stripes);
-            debugln!("{: >20} Match: {}", "", towel.stripes);
-            let remaining = &pattern[towel.stripes.len()..];
-
-            let r = find_pattern(remaining, towels, cache);
-            cache.insert(remaining.to_string(), r);
-
-            result += r;
-        }
-    }
+        towels
+            .iter()
+            .filter(|towel| pattern.starts_with(&towel.stripes))
+            .map(|towel| {
+                debugln!("{: >20} Match: {}", "", towel.stripes);
 
-    // debugln!("{: >20} Nope", "");
-    cache.insert(pattern.to_string(), result);
-    result
+                find_pattern(&pattern[towel.stripes.len()..], towels, cache)
+            })
+            .sum()
+    })
 }
 
 #[cfg(test)]
@@ -1,9 +1,6 @@
-use std::{
-    cmp::Reverse,
-    collections::{BinaryHeap, HashMap, HashSet},
-};
+use std::{cmp::Reverse, collections::BinaryHeap};
 
-use lib::{Grid2d, Point};
+use lib::{Grid2d, Point, PointMap, PointSet};
 
 #[derive(PartialEq, Eq)]
 pub enum Tile {
@@ -115,21 +112,21 @@ impl Maze {
             .collect()
     }
 
-    fn dijkstra(&self, start: &Point) -> HashMap<Point, u64> {
-        let mut visited: HashSet<Point> = HashSet::new();
+    fn dijkstra(&self, start: &Point) -> PointMap<u64> {
+        let mut visited = PointSet::for_grid(&self.grid);
         let mut to_visit: BinaryHeap<Reverse<(u64, Point)>> = BinaryHeap::new();
-        let mut distances: HashMap<Point, u64> = HashMap::new();
+        let mut distances: PointMap<u64> = PointMap::for_grid(&self.grid);
 
         to_visit.push(Reverse((0, *start)));
-        distances.insert(*start, 0);
+        if distances.insert(*start, 0).is_err() {
+            return distances;
+        }
 
         while let Some(Reverse((_, pos))) = to_visit.pop() {
-            if visited.contains(&pos) {
+            if visited.insert(pos) != Ok(true) {
                 continue;
             }
 
-            visited.insert(pos);
-
             let current_distance = *distances.get(&pos).unwrap_or(&u64::MAX);
 
             for (_, neighboor_pos, _) in self
@@ -142,9 +139,11 @@ impl Maze {
 
                 let new_neighboor_distance = current_distance.saturating_add(1);
 
-                if new_neighboor_distance <= existing_neighboor_distance {
-                    *distances.entry(neighboor_pos).or_insert(u64::MAX) = new_neighboor_distance;
-
+                if new_neighboor_distance <= existing_neighboor_distance
+                    && distances
+                        .insert(neighboor_pos, new_neighboor_distance)
+                        .is_ok()
+                {
                     to_visit.push(Reverse((new_neighboor_distance, neighboor_pos)));
                 }
             }
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use crate::keypads::{key_pairs, Keypad};
+
+// Too large for a u128, costs saturate at it
+const OVERFLOW: u128 = u128::MAX;
+
+// Pair costs indexed by `from * keys + to`
+type Vector = Vec<u128>;
+
+// A keypad commanded through any number of robots all on the same directional keypad.
+//
+// Each level is computed exactly from the one above: pressing `to` after `from` costs the
+// cheapest of the arm moves between them, a move costing the sum of its key pairs one level up.
+// Levels are computed one by one until they stop changing, which happens once every cost either
+// stopped growing or saturated at `OVERFLOW`. Costs grow exponentially with the depth, so that's
+// within about a hundred levels for the directional keypad, any deeper level being the same.
+//
+// A u128 only holds about 90 levels of the directional keypad, deeper chains are unsupported.
+pub struct DeepChain {
+    code_keypad: Keypad,
+    keypad: Keypad,
+    // Keys of `keypad`, their index being the one of the costs
+    keys: Vec<char>,
+    // Arm moves of each pair, pressing the key at the end
+    moves: Vec<Vec<String>>,
+    // What each key the human presses costs, 1 if missing
+    press_costs: HashMap<char, u128>,
+}
+
+impl DeepChain {
+    pub fn new(code_keypad: Keypad, keypad: Keypad) -> Self {
+        let mut chain = Self {
+            code_keypad,
+            keys: keypad.keys(),
+            moves: vec![],
+            keypad,
+            press_costs: HashMap::new(),
+        };
+
+        chain.moves = chain
+            .pairs()
+            .map(|(from, to)| {
+                chain
+                    .keypad
+                    .paths(from, to)
+                    .into_iter()
+                    .map(|path| path + "A")
+                    .collect()
+            })
+            .collect();
+
+        chain
+    }
+
+    pub fn with_press_costs(mut self, press_costs: HashMap<char, u128>) -> Self {
+        self.press_costs = press_costs;
+
+        self
+    }
+
+    // Cost of the cheapest sequence typing `code` through `depth` directional keypads, None if
+    // it doesn't fit a u128
+    pub fn find_shortest_sequence_cost(&self, code: &str, depth: u64) -> Option<u128> {
+        let costs = self.costs_at(depth);
+
+        let cost = key_pairs(code)
+            .into_iter()
+            .map(|(from, to)| {
+                self.code_moves(from, to)
+                    .iter()
+                    .map(|moves| self.sequence_cost(moves, &costs))
+                    .min()
+                    .unwrap()
+            })
+            .fold(0, u128::saturating_add);
+
+        (cost != OVERFLOW).then_some(cost)
+    }
+
+    // Pair costs `depth` levels down, stopping early once a level is the same as the previous one
+    fn costs_at(&self, depth: u64) -> Vector {
+        // What the human presses
+        let mut costs: Vector = self
+            .pairs()
+            .map(|(_, to)| self.press_costs.get(&to).copied().unwrap_or(1))
+            .collect();
+
+        for _ in 0..depth {
+            let next = self.level(&costs);
+
+            if next == costs {
+                break;
+            }
+
+            costs = next;
+        }
+
+        costs
+    }
+
+    // Arm moves on the code keypad, pressing `to` at the end
+    fn code_moves(&self, from: char, to: char) -> Vec<String> {
+        self.code_keypad
+            .paths(from, to)
+            .into_iter()
+            .map(|path| path + "A")
+            .collect()
+    }
+
+    // Costs one level down
+    fn level(&self, costs: &Vector) -> Vector {
+        self.moves
+            .iter()
+            .map(|moves| {
+                moves
+                    .iter()
+                    .map(|moves| self.sequence_cost(moves, costs))
+                    .min()
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    fn sequence_cost(&self, sequence: &str, costs: &Vector) -> u128 {
+        key_pairs(sequence)
+            .into_iter()
+            .map(|(from, to)| costs[self.index(from, to)])
+            .fold(0, u128::saturating_add)
+    }
+
+    fn pairs(&self) -> impl Iterator<Item = (char, char)> + '_ {
+        self.keys
+            .iter()
+            .flat_map(|from| self.keys.iter().map(move |to| (*from, *to)))
+    }
+
+    fn index(&self, from: char, to: char) -> usize {
+        let position = |key: char| self.keys.binary_search(&key).unwrap();
+
+        position(from) * self.keys.len() + position(to)
+    }
+}
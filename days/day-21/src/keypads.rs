@@ -1,69 +1,193 @@
-use std::{collections::HashMap, fmt::format};
-
-use lib::{debugln, Point};
-
-type Keypad = HashMap<char, Point>;
-
-fn new_numeric_keypad() -> Keypad {
-    [
-        ['7', '8', '9'],
-        ['4', '5', '6'],
-        ['1', '2', '3'],
-        [' ', '0', 'A'],
-    ]
-    .iter()
-    .enumerate()
-    .flat_map(|(i, row)| {
-        row.iter()
-            .enumerate()
-            .map(move |(j, c)| (*c, (i as i64, j as i64).into()))
-    })
-    .collect()
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    rc::Rc,
+};
+
+use lib::{debugln, memo::Memo, Direction, Point};
+
+// Arm moves and the key commanding them
+const STEPS: [(Direction, char); 4] = [
+    (Direction::Up, '^'),
+    (Direction::Down, 'v'),
+    (Direction::Left, '<'),
+    (Direction::Right, '>'),
+];
+
+#[derive(Debug, Clone)]
+pub struct Keypad {
+    keys: HashMap<char, Point>,
+    cells: HashMap<Point, char>,
+    // The arm can't move over a gap, nor outside of the keys
+    gaps: HashSet<Point>,
 }
 
-fn new_directional_keypad() -> Keypad {
-    [[' ', '^', 'A'], ['<', 'v', '>']]
-        .iter()
-        .enumerate()
-        .flat_map(|(i, row)| {
-            row.iter()
-                .enumerate()
-                .map(move |(j, c)| (*c, (i as i64, j as i64).into()))
-        })
-        .collect()
+impl Keypad {
+    // One row per line and one key per character, `#` or a space marking a gap. The arm starts on
+    // the `A` key.
+    pub fn parse(layout: &str) -> Self {
+        let mut keypad = Keypad {
+            keys: HashMap::new(),
+            cells: HashMap::new(),
+            gaps: HashSet::new(),
+        };
+
+        for (i, line) in layout.lines().enumerate() {
+            for (j, c) in line.chars().enumerate() {
+                let position: Point = (i as i64, j as i64).into();
+
+                if c == '#' || c == ' ' {
+                    keypad.gaps.insert(position);
+                } else if keypad.keys.insert(c, position).is_some() {
+                    panic!("Duplicated key {:?}", c);
+                } else {
+                    keypad.cells.insert(position, c);
+                }
+            }
+        }
+
+        assert!(keypad.keys.contains_key(&'A'), "The keypad needs an A key");
+
+        keypad
+    }
+
+    pub fn numeric() -> Self {
+        Self::parse("789\n456\n123\n#0A")
+    }
+
+    pub fn directional() -> Self {
+        Self::parse("#^A\n<v>")
+    }
+
+    fn position(&self, key: char) -> Point {
+        *self
+            .keys
+            .get(&key)
+            .unwrap_or_else(|| panic!("No key {:?} on the keypad", key))
+    }
+
+    fn key_at(&self, position: Point) -> Option<char> {
+        self.cells.get(&position).copied()
+    }
+
+    // Keys, sorted
+    pub fn keys(&self) -> Vec<char> {
+        let mut keys: Vec<char> = self.keys.keys().copied().collect();
+        keys.sort();
+
+        keys
+    }
+
+    // Every shortest arm move from `from` to `to`, vertical moves first. Longer ones are never
+    // cheaper, as each extra move is an extra press above.
+    pub fn paths(&self, from: char, to: char) -> Vec<String> {
+        let target = self.position(to);
+
+        // Distances to the target, walking over keys only
+        let mut distances = HashMap::from([(target, 0)]);
+        let mut queue = VecDeque::from([target]);
+
+        while let Some(position) = queue.pop_front() {
+            for (direction, _) in STEPS {
+                let next = position + direction.into();
+
+                if self.cells.contains_key(&next) && !distances.contains_key(&next) {
+                    distances.insert(next, distances[&position] + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let start = self.position(from);
+
+        assert!(
+            distances.contains_key(&start),
+            "{:?} can't be reached from {:?}",
+            to,
+            from
+        );
+
+        let mut paths = vec![];
+        let mut stack = vec![(start, String::new())];
+
+        while let Some((position, path)) = stack.pop() {
+            if position == target {
+                paths.push(path);
+                continue;
+            }
+
+            for (direction, key) in STEPS.iter().rev() {
+                let next = position + direction.into();
+
+                if distances.get(&next) == Some(&(distances[&position] - 1)) {
+                    stack.push((next, format!("{path}{key}")));
+                }
+            }
+        }
+
+        paths
+    }
 }
 
 pub struct KeypadChain {
-    numeric_keypad: Keypad,
-    directional_keypad: Keypad,
-    n_directional_keypads: usize,
+    // From the keypad the code is typed on, each next one being where the robot of the previous
+    // one is commanded from. The human commands the last robot.
+    keypads: Vec<Keypad>,
 
-    path_cost_cache: HashMap<(usize, char, char), usize>,
+    // What each key the human presses costs, 1 if missing
+    press_costs: HashMap<char, usize>,
+
+    path_cost_cache: Memo<(usize, char, char), usize>,
 }
 
 impl KeypadChain {
+    // The numeric keypad then `n_directional_keypads` directional ones
     pub fn new(n_directional_keypads: usize) -> Self {
+        Self::with_layouts(
+            std::iter::once(Keypad::numeric())
+                .chain(std::iter::repeat_n(
+                    Keypad::directional(),
+                    n_directional_keypads,
+                ))
+                .collect(),
+        )
+    }
+
+    pub fn with_layouts(keypads: Vec<Keypad>) -> Self {
+        assert!(!keypads.is_empty(), "At least the keypad of the code");
+
+        for keypad in keypads.iter().skip(1) {
+            for (_, key) in STEPS {
+                assert!(
+                    keypad.keys.contains_key(&key),
+                    "Keypads commanding a robot need the {:?} key",
+                    key
+                );
+            }
+        }
+
         Self {
-            numeric_keypad: new_numeric_keypad(),
-            directional_keypad: new_directional_keypad(),
-            n_directional_keypads,
+            keypads,
+            press_costs: HashMap::new(),
 
-            path_cost_cache: HashMap::new(),
+            path_cost_cache: Memo::new(),
         }
     }
 
+    pub fn with_press_costs(mut self, press_costs: HashMap<char, usize>) -> Self {
+        self.press_costs = press_costs;
+        self.path_cost_cache.clear();
+
+        self
+    }
+
+    // Cost of what the human types, its length with the default press costs
     pub fn find_shortest_sequence_length(&mut self, code: &str) -> usize {
-        self.keypresses_cost(code, self.n_directional_keypads + 1)
+        self.keypresses_cost(code, self.keypads.len())
     }
 
     fn keypresses_cost(&mut self, code: &str, n_robots: usize) -> usize {
-        // We start on A, we need to sum the cost of each code part A -> 1, 1 -> 2
-        let code = "A".to_string() + code;
-
-        let left = code.chars().take(code.len() - 1);
-        let right = code.chars().skip(1);
-
-        left.zip(right)
+        key_pairs(code)
+            .into_iter()
             .map(|(from, to)| self.path_cost(from, to, n_robots))
             .sum()
     }
@@ -71,10 +195,10 @@ impl KeypadChain {
     fn path_cost(&mut self, from: char, to: char, n_robots: usize) -> usize {
         // On the last keypad we can input directly
         if n_robots == 0 {
-            return 1;
+            return self.press_costs.get(&to).copied().unwrap_or(1);
         }
 
-        let indent_size = self.n_directional_keypads + 1 - n_robots;
+        let indent_size = self.keypads.len() - n_robots;
         let indent = "\t".repeat(indent_size);
 
         debugln!(
@@ -85,7 +209,7 @@ impl KeypadChain {
             to
         );
 
-        if let Some(&result) = self.path_cost_cache.get(&(n_robots, from, to)) {
+        if let Some(result) = self.path_cost_cache.get(&(n_robots, from, to)) {
             debugln!(
                 "\t{}[CACHE] Path cost hit ({:?}) = {}",
                 indent,
@@ -95,63 +219,187 @@ impl KeypadChain {
             return result;
         }
 
-        let keypad = if n_robots > self.n_directional_keypads {
-            &self.numeric_keypad
-        } else {
-            &self.directional_keypad
-        };
+        let result = self
+            .moves(from, to, n_robots)
+            .iter()
+            .map(|moves| self.keypresses_cost(moves, n_robots - 1))
+            .min()
+            .unwrap();
 
-        let from_coord = keypad[&from];
-        let to_coord = keypad[&to];
-        let impossible_coord: Point = keypad[&' '];
-        let vector = to_coord - from_coord;
+        self.path_cost_cache.insert((n_robots, from, to), result);
 
-        let vertical_steps =
-            if vector.x > 0 { "v" } else { "^" }.repeat(vector.x.unsigned_abs() as usize);
-        let horizontal_steps =
-            if vector.y > 0 { ">" } else { "<" }.repeat(vector.y.unsigned_abs() as usize);
+        debugln!("{}<==", indent);
+        result
+    }
 
-        // It is generally better to have continous sequence of move >>^^ instance of >^>^ to
-        // reduce move count
-        let vertical_first = format!("{vertical_steps}{horizontal_steps}A");
-        let horizontal_first = format!("{horizontal_steps}{vertical_steps}A");
+    // One optimal sequence per keypad, from the code to what the human types, each one typing the
+    // previous one. Their length grows exponentially with the number of robots.
+    pub fn find_shortest_sequences(&mut self, code: &str) -> Vec<String> {
+        let mut sequences = vec![code.to_string()];
 
-        debugln!(
-            "{}\t{:?} -> {:?} = {:?}",
-            indent,
-            from_coord,
-            to_coord,
-            vector
-        );
-        debugln!("{}\tVF: {}", indent, vertical_first);
-        debugln!("{}\tHF: {}", indent, horizontal_first);
-
-        // We need to check that on the corner we don't go through the empty cell
-        let vertical_first_cost = if impossible_coord == (to_coord.x, from_coord.y).into() {
-            usize::MAX
-        } else {
-            self.keypresses_cost(&vertical_first, n_robots - 1)
-        };
+        for n_robots in (1..=self.keypads.len()).rev() {
+            let sequence = key_pairs(sequences.last().unwrap())
+                .into_iter()
+                .map(|(from, to)| self.optimal_moves(from, to, n_robots).swap_remove(0))
+                .collect();
 
-        let horizontal_first_cost = if vertical_first == horizontal_first
-            || impossible_coord == (from_coord.x, to_coord.y).into()
-        {
-            usize::MAX
-        } else {
-            self.keypresses_cost(&horizontal_first, n_robots - 1)
+            sequences.push(sequence);
+        }
+
+        sequences
+    }
+
+    // Every optimal sequence the human can type, generated lazily
+    pub fn all_shortest_sequences(&mut self, code: &str) -> impl Iterator<Item = String> {
+        let n_robots = self.keypads.len();
+
+        // The generation only reads the optimal moves, computed for every pair of keys first
+        let mut table = HashMap::new();
+        self.collect_optimal_moves(code, n_robots, &mut table);
+
+        sequences(Rc::new(table), key_pairs(code), n_robots)
+    }
+
+    fn collect_optimal_moves(&mut self, sequence: &str, n_robots: usize, table: &mut MovesTable) {
+        if n_robots == 0 {
+            return;
+        }
+
+        for (from, to) in key_pairs(sequence) {
+            if table.contains_key(&(n_robots, from, to)) {
+                continue;
+            }
+
+            let moves = self.optimal_moves(from, to, n_robots);
+
+            for sequence in moves.iter() {
+                self.collect_optimal_moves(sequence, n_robots - 1, table);
+            }
+
+            table.insert((n_robots, from, to), moves);
+        }
+    }
+
+    // Moves among `moves` costing the least to type for the robots above
+    fn optimal_moves(&mut self, from: char, to: char, n_robots: usize) -> Vec<String> {
+        let cost = self.path_cost(from, to, n_robots);
+
+        self.moves(from, to, n_robots)
+            .into_iter()
+            .filter(|moves| self.keypresses_cost(moves, n_robots - 1) == cost)
+            .collect()
+    }
+
+    // Presses moving the arm from `from` to `to` then pressing it
+    fn moves(&self, from: char, to: char, n_robots: usize) -> Vec<String> {
+        self.keypad(n_robots)
+            .paths(from, to)
+            .into_iter()
+            .map(|path| path + "A")
+            .collect()
+    }
+
+    fn keypad(&self, n_robots: usize) -> &Keypad {
+        &self.keypads[self.keypads.len() - n_robots]
+    }
+
+    // Plays what the human types through every robot, returning what the last one types on the
+    // numeric keypad
+    pub fn replay(&self, sequence: &str) -> Result<String, ReplayError> {
+        let mut typed = sequence.to_string();
+
+        for n_robots in 1..=self.keypads.len() {
+            typed = replay_keypad(self.keypad(n_robots), &typed).map_err(|(step, kind)| {
+                ReplayError {
+                    n_robots,
+                    step,
+                    kind,
+                }
+            })?;
+        }
+
+        Ok(typed)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReplayErrorKind {
+    InvalidKey(char),
+    // The arm points at the gap
+    Gap,
+    OffKeypad,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReplayError {
+    // Robot (counted from the human) whose arm fails
+    pub n_robots: usize,
+    // Index of the failing press in what that robot is told to type
+    pub step: usize,
+    pub kind: ReplayErrorKind,
+}
+
+fn replay_keypad(keypad: &Keypad, sequence: &str) -> Result<String, (usize, ReplayErrorKind)> {
+    let mut position = keypad.position('A');
+    let mut typed = String::new();
+
+    for (step, c) in sequence.chars().enumerate() {
+        if c == 'A' {
+            typed.push(keypad.key_at(position).unwrap());
+            continue;
+        }
+
+        let Some((direction, _)) = STEPS.iter().find(|(_, key)| *key == c) else {
+            return Err((step, ReplayErrorKind::InvalidKey(c)));
         };
 
-        debugln!(
-            "{}\tVF = {}, HF = {}",
-            indent,
-            vertical_first_cost,
-            horizontal_first_cost
-        );
+        position += direction.into();
 
-        let result = vertical_first_cost.min(horizontal_first_cost);
-        self.path_cost_cache.insert((n_robots, from, to), result);
+        if keypad.gaps.contains(&position) {
+            return Err((step, ReplayErrorKind::Gap));
+        }
 
-        debugln!("{}<==", indent);
-        result
+        if keypad.key_at(position).is_none() {
+            return Err((step, ReplayErrorKind::OffKeypad));
+        }
+    }
+
+    Ok(typed)
+}
+
+// We start on A, every key being reached from the previous one: A -> 1, 1 -> 2
+pub(super) fn key_pairs(sequence: &str) -> Vec<(char, char)> {
+    let keys: Vec<char> = "A".chars().chain(sequence.chars()).collect();
+
+    keys.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+// Optimal moves by (n_robots, from, to)
+type MovesTable = HashMap<(usize, char, char), Vec<String>>;
+
+// Sequences typing `keys` with `n_robots` robots left, every optimal move of the first key
+// followed by every sequence typing the others
+fn sequences(
+    table: Rc<MovesTable>,
+    keys: Vec<(char, char)>,
+    n_robots: usize,
+) -> Box<dyn Iterator<Item = String>> {
+    if n_robots == 0 {
+        return Box::new(std::iter::once(keys.iter().map(|(_, to)| *to).collect()));
     }
+
+    let Some((&(from, to), rest)) = keys.split_first() else {
+        return Box::new(std::iter::once(String::new()));
+    };
+
+    let rest = rest.to_vec();
+    let moves = table[&(n_robots, from, to)].clone();
+
+    Box::new(moves.into_iter().flat_map(move |moves| {
+        let (table, rest) = (table.clone(), rest.clone());
+
+        sequences(table.clone(), key_pairs(&moves), n_robots - 1).flat_map(move |head| {
+            sequences(table.clone(), rest.clone(), n_robots).map(move |tail| head.clone() + &tail)
+        })
+    }))
 }
@@ -1,16 +1,41 @@
-use std::{fs, time::Instant};
+use std::{collections::HashMap, fs, time::Instant};
 
-use keypads::KeypadChain;
-use lib::{Direction, Point};
+use deep::DeepChain;
+use keypads::{Keypad, KeypadChain};
 
+mod deep;
 mod keypads;
 
 type ParsedInput = Vec<(String, u64)>;
 type Output = u64;
 
 fn main() {
+    // Show the presses typing a code instead of solving, e.g. `cargo run -- --sequences 029A 2`.
+    // The human presses can be given costs, e.g. `cargo run -- --sequences 029A 2 '<:3,^:2'`.
+    if std::env::args().nth(1).as_deref() == Some("--sequences") {
+        let code = std::env::args().nth(2).unwrap_or("029A".to_string());
+        let n_robots = std::env::args().nth(3).map_or(2, |n| n.parse().unwrap());
+        let press_costs = parse_press_costs(std::env::args().nth(4).as_deref());
+
+        return sequences(&code, n_robots, press_costs);
+    }
+
     let input = input();
 
+    // Part 2 with any number of robots, e.g. `cargo run -- --depth 1000000`
+    if std::env::args().nth(1).as_deref() == Some("--depth") {
+        let depth = std::env::args().nth(2).map_or(25, |n| n.parse().unwrap());
+        let press_costs = parse_press_costs(std::env::args().nth(3).as_deref());
+
+        return match complexity_at_depth(&input, depth, press_costs) {
+            Some(complexity) => println!("Complexity at depth {}: {}", depth, complexity),
+            None => println!(
+                "Complexity at depth {}: too large for a u128, unsupported",
+                depth
+            ),
+        };
+    }
+
     let start = Instant::now();
     println!("Result (part 1): {} [{:?}]", part1(&input), start.elapsed());
 
@@ -18,6 +43,39 @@ fn main() {
     println!("Result (part 2): {} [{:?}]", part2(&input), start.elapsed());
 }
 
+// `key:cost` pairs separated by commas, missing keys costing 1
+fn parse_press_costs(press_costs: Option<&str>) -> HashMap<char, usize> {
+    press_costs
+        .unwrap_or_default()
+        .split(',')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, cost) = pair.split_once(':').unwrap();
+
+            (key.chars().next().unwrap(), cost.parse().unwrap())
+        })
+        .collect()
+}
+
+// One optimal sequence per keypad, checked by replaying it, then a few other optimal ones
+fn sequences(code: &str, n_directional_keypads: usize, press_costs: HashMap<char, usize>) {
+    let mut keypad_chain = KeypadChain::new(n_directional_keypads).with_press_costs(press_costs);
+    let sequences = keypad_chain.find_shortest_sequences(code);
+
+    for sequence in sequences.iter() {
+        println!("{} ({})", sequence, sequence.len());
+    }
+
+    println!(
+        "Replay: {:?}",
+        keypad_chain.replay(sequences.last().unwrap())
+    );
+
+    for sequence in keypad_chain.all_shortest_sequences(code).take(5) {
+        println!("Also optimal: {}", sequence);
+    }
+}
+
 fn input() -> ParsedInput {
     let input = fs::read_to_string("inputs/day-21.txt").unwrap();
     parse_input(&input)
@@ -57,63 +115,34 @@ fn part2(input: &ParsedInput) -> Output {
         .sum()
 }
 
-fn assert_numeric_sequence(seq: &str) {
-    let mut pos: Point = (3, 2).into();
-    let impossible: Point = (3, 0).into();
-
-    for c in seq.chars() {
-        match c {
-            '<' => {
-                pos += Direction::Left.into();
-            }
-            '>' => {
-                pos += Direction::Right.into();
-            }
-            '^' => {
-                pos += Direction::Up.into();
-            }
-            'v' => {
-                pos += Direction::Down.into();
-            }
-            _ => {}
-        };
+fn complexity_at_depth(
+    input: &ParsedInput,
+    depth: u64,
+    press_costs: HashMap<char, usize>,
+) -> Option<u128> {
+    let chain = deep_chain(press_costs);
 
-        if pos == impossible {
-            panic!("Invalid sequence: {}", seq);
-        }
-    }
+    input.iter().try_fold(0u128, |sum, (code, n)| {
+        chain
+            .find_shortest_sequence_cost(code, depth)?
+            .checked_mul(*n as u128)?
+            .checked_add(sum)
+    })
 }
 
-fn assert_directional_sequence(seq: &str) {
-    let mut pos: Point = (0, 2).into();
-    let impossible: Point = (0, 0).into();
-
-    for c in seq.chars() {
-        match c {
-            '<' => {
-                pos += Direction::Left.into();
-            }
-            '>' => {
-                pos += Direction::Right.into();
-            }
-            '^' => {
-                pos += Direction::Up.into();
-            }
-            'v' => {
-                pos += Direction::Down.into();
-            }
-            _ => {}
-        };
-
-        if pos == impossible {
-            panic!("Invalid sequence");
-        }
-    }
+fn deep_chain(press_costs: HashMap<char, usize>) -> DeepChain {
+    DeepChain::new(Keypad::numeric(), Keypad::directional()).with_press_costs(
+        press_costs
+            .into_iter()
+            .map(|(key, cost)| (key, cost as u128))
+            .collect(),
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use keypads::{ReplayError, ReplayErrorKind};
 
     fn input() -> ParsedInput {
         parse_input(
@@ -153,6 +182,184 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_shortest_sequences() {
+        for code in ["029A", "980A", "179A", "456A", "379A"] {
+            let mut keypad_chain = KeypadChain::new(2);
+            let sequences = keypad_chain.find_shortest_sequences(code);
+
+            assert_eq!(sequences.len(), 4);
+            assert_eq!(sequences[0], code);
+            assert_eq!(
+                sequences[3].len(),
+                keypad_chain.find_shortest_sequence_length(code)
+            );
+            assert_eq!(keypad_chain.replay(&sequences[3]).unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn test_all_shortest_sequences() {
+        // Without robots, the zigzag ^>^ is as good as the others
+        assert_eq!(
+            KeypadChain::new(0)
+                .all_shortest_sequences("029A")
+                .collect::<Vec<String>>(),
+            vec!["<A^A^^>AvvvA", "<A^A^>^AvvvA", "<A^A>^^AvvvA"]
+        );
+
+        let mut keypad_chain = KeypadChain::new(2);
+        let length = keypad_chain.find_shortest_sequence_length("379A");
+        let sequences: Vec<String> = keypad_chain.all_shortest_sequences("379A").collect();
+
+        assert!(sequences.len() > 1);
+        assert!(sequences
+            .iter()
+            .all(|sequence| sequence.len() == length
+                && keypad_chain.replay(sequence).unwrap() == "379A"));
+    }
+
+    #[test]
+    fn test_layouts() {
+        // Going from A to 1 needs three turns around the gaps, the arm walking through 6, 4 and 2
+        let mut keypad_chain = KeypadChain::with_layouts(vec![
+            Keypad::parse("123\n#4#\n56A"),
+            Keypad::directional(),
+            Keypad::parse("<v^>A"),
+        ]);
+
+        let sequences = keypad_chain.find_shortest_sequences("1A");
+
+        assert_eq!(sequences[1], "<^^<A>vv>A");
+        assert_eq!(
+            sequences[3].len(),
+            keypad_chain.find_shortest_sequence_length("1A")
+        );
+        assert_eq!(keypad_chain.replay(&sequences[3]).unwrap(), "1A");
+        assert_eq!(
+            keypad_chain.replay("<<<<<"),
+            Err(ReplayError {
+                n_robots: 1,
+                step: 4,
+                kind: ReplayErrorKind::OffKeypad
+            })
+        );
+
+        // Same as the default chain
+        let mut keypad_chain = KeypadChain::with_layouts(vec![
+            Keypad::parse("789\n456\n123\n 0A"),
+            Keypad::parse(" ^A\n<v>"),
+            Keypad::parse(" ^A\n<v>"),
+        ]);
+
+        assert_eq!(keypad_chain.find_shortest_sequence_length("029A"), 68);
+    }
+
+    #[test]
+    fn test_deep_chain() {
+        let chain = DeepChain::new(Keypad::numeric(), Keypad::directional());
+
+        for depth in 0..40 {
+            assert_eq!(
+                chain.find_shortest_sequence_cost("456A", depth),
+                Some(
+                    KeypadChain::new(depth as usize).find_shortest_sequence_length("456A") as u128
+                )
+            );
+        }
+
+        assert_eq!(
+            complexity_at_depth(&input(), 25, HashMap::new()),
+            Some(154115708116294)
+        );
+
+        // Each level is about 2.5 times the previous one: the last ones fitting a u128, then
+        // unsupported whatever the depth
+        let deepest = (0..200)
+            .take_while(|depth| chain.find_shortest_sequence_cost("029A", *depth).is_some())
+            .last()
+            .unwrap();
+        let cost = chain.find_shortest_sequence_cost("029A", deepest).unwrap();
+
+        assert!(cost > u128::MAX / 4);
+        assert!(
+            chain
+                .find_shortest_sequence_cost("029A", deepest - 1)
+                .unwrap()
+                < cost
+        );
+        assert_eq!(chain.find_shortest_sequence_cost("029A", deepest + 1), None);
+        assert_eq!(chain.find_shortest_sequence_cost("029A", 1_000_000), None);
+        assert_eq!(chain.find_shortest_sequence_cost("029A", u64::MAX), None);
+
+        // Free presses, whatever the depth
+        let chain = chain.with_press_costs(HashMap::from([
+            ('<', 0),
+            ('>', 0),
+            ('^', 0),
+            ('v', 0),
+            ('A', 0),
+        ]));
+        assert_eq!(
+            chain.find_shortest_sequence_cost("029A", 1_000_000),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_press_costs() {
+        let press_costs = HashMap::from([('<', 5), ('>', 1), ('^', 3), ('v', 2), ('A', 1)]);
+        let chain = DeepChain::new(Keypad::numeric(), Keypad::directional())
+            .with_press_costs(press_costs.iter().map(|(k, v)| (*k, *v as u128)).collect());
+
+        for depth in 0..12 {
+            let mut keypad_chain = KeypadChain::new(depth).with_press_costs(press_costs.clone());
+
+            assert_eq!(
+                chain.find_shortest_sequence_cost("379A", depth as u64),
+                Some(keypad_chain.find_shortest_sequence_length("379A") as u128)
+            );
+        }
+
+        // The cheapest sequence isn't always the shortest one any more
+        let mut keypad_chain = KeypadChain::new(3).with_press_costs(press_costs.clone());
+        let sequence = keypad_chain.find_shortest_sequences("379A").pop().unwrap();
+        let cost: usize = sequence.chars().map(|key| press_costs[&key]).sum();
+
+        assert_eq!(keypad_chain.find_shortest_sequence_length("379A"), cost);
+        assert_eq!(keypad_chain.replay(&sequence).unwrap(), "379A");
+    }
+
+    #[test]
+    fn test_replay() {
+        let keypad_chain = KeypadChain::new(2);
+
+        assert_eq!(
+            keypad_chain
+                .replay("<vA<AA>>^AvAA<^A>A<v<A>>^AvA^A<vA>^A<v<A>^A>AAvA^A<v<A>A>^AAAvA<^A>A")
+                .unwrap(),
+            "029A"
+        );
+
+        // The first robot goes left twice from A, onto the gap
+        assert_eq!(
+            keypad_chain.replay("v<<AA>>^A"),
+            Err(ReplayError {
+                n_robots: 2,
+                step: 1,
+                kind: ReplayErrorKind::Gap
+            })
+        );
+        assert_eq!(
+            keypad_chain.replay("^A"),
+            Err(ReplayError {
+                n_robots: 1,
+                step: 0,
+                kind: ReplayErrorKind::OffKeypad
+            })
+        );
+    }
+
     #[test]
     fn test_part1() {
         assert_eq!(part1(&input()), 126384);
@@ -1,125 +1,57 @@
-use std::{
-    collections::{HashMap, HashSet},
-    rc::Rc,
+use lib::{
+    export::ExportOptions,
+    graph::{Cliques, Graph, NodeId},
 };
 
-use lib::debugln;
-
-pub struct Graph {
-    pub nodes: HashSet<Rc<String>>,
-    pub links: HashMap<Rc<String>, HashSet<Rc<String>>>,
+// Computers of the LAN party, linked when they're directly connected
+pub struct Network {
+    graph: Graph<String>,
 }
 
-impl Graph {
+impl Network {
     pub fn from(input: &str) -> Self {
-        let mut graph = Graph {
-            nodes: HashSet::new(),
-            links: HashMap::new(),
-        };
+        let mut graph = Graph::undirected();
 
         for line in input.lines() {
             let (a, b) = line.split_once("-").unwrap();
 
-            let a_idx = Rc::new(a.to_string());
-            let b_idx = Rc::new(b.to_string());
-
-            graph.insert(&a_idx);
-            graph.insert(&b_idx);
-
-            graph.link(&a_idx, &b_idx);
-            graph.link(&b_idx, &a_idx);
+            graph.connect(a.to_string(), b.to_string(), ());
         }
 
-        graph
+        Network { graph }
     }
 
-    pub fn insert(&mut self, index: &Rc<String>) {
-        if !self.nodes.contains(index) {
-            self.nodes.insert(index.clone());
-        }
+    // Names of the largest set of computers all connected to each other, sorted
+    pub fn max_clique(&self) -> Vec<&str> {
+        self.names(&self.graph.max_clique())
     }
 
-    pub fn link(&mut self, a: &Rc<String>, b: &Rc<String>) {
-        let node_links = self.links.entry(a.clone()).or_default();
-
-        if !node_links.contains(b) {
-            node_links.insert(b.clone());
-        }
+    // Every set of `size` computers all connected to each other
+    pub fn cliques_of_size(&self, size: usize) -> Cliques<'_, String, ()> {
+        self.graph.cliques_of_size(size)
     }
 
-    pub fn find_largest_connected_set(&self) -> (usize, HashSet<Rc<String>>) {
-        let cliques = self.bron_kerbosch();
-
-        let max_clique = cliques
-            .into_iter()
-            .max_by_key(|clique| clique.len())
-            .unwrap();
-
-        (max_clique.len(), max_clique)
+    pub fn to_dot(&self) -> String {
+        self.graph.to_dot(&self.export_options())
     }
 
-    pub fn bron_kerbosch(&self) -> Vec<HashSet<Rc<String>>> {
-        self._bron_kerbosch(&HashSet::new(), &self.nodes, &HashSet::new())
+    pub fn to_mermaid(&self) -> String {
+        self.graph.to_mermaid(&self.export_options())
     }
 
-    fn _bron_kerbosch(
-        &self,
-        r: &HashSet<Rc<String>>,
-        p: &HashSet<Rc<String>>,
-        x: &HashSet<Rc<String>>,
-    ) -> Vec<HashSet<Rc<String>>> {
-        debugln!("BK: r={:?}, p={:?}, x={:?}", r, p, x);
-
-        if p.is_empty() && x.is_empty() {
-            debugln!("<- {:?}", r);
-            return vec![r.clone()];
-        }
-
-        if p.is_empty() {
-            return vec![];
-        }
-
-        let pivot_u = p
-            .iter()
-            .max_by_key(|&node| self.links.get(node).map(|l| l.len()).unwrap_or(0))
-            .unwrap();
-
-        debugln!("\tu = {}", pivot_u);
-
-        let n_u = self.links.get(pivot_u).unwrap();
-
-        debugln!("\tn_u = {:?}", n_u);
-
-        let mut results = vec![];
-        let mut p = p.clone();
-        let mut x = x.clone();
+    // The largest clique is highlighted
+    fn export_options(&self) -> ExportOptions<'static, String, ()> {
+        ExportOptions::new(|name: &String| name.clone()).highlight(self.graph.max_clique(), "red")
+    }
 
-        for v in p
+    pub fn names(&self, clique: &[NodeId]) -> Vec<&str> {
+        let mut names: Vec<&str> = clique
             .iter()
-            .filter(|&n| !n_u.contains(n))
-            .cloned()
-            .collect::<Vec<Rc<String>>>()
-        {
-            let n_v = self.links.get(&v).unwrap();
-
-            debugln!("v = {}", v);
-            debugln!("n_v = {:?}", n_v);
-
-            let mut new_r = r.clone();
-            new_r.insert(v.clone());
-
-            let new_p = p.iter().filter(|&v| n_v.contains(v)).cloned().collect();
-            let new_x = x.iter().filter(|&v| n_v.contains(v)).cloned().collect();
-
-            debugln!("-> r={:?}, p={:?}, x={:?}", new_r, new_p, new_x);
-
-            results.append(&mut self._bron_kerbosch(&new_r, &new_p, &new_x));
-
-            p.remove(&v);
-            x.insert(v.clone());
-        }
+            .map(|id| self.graph.node(*id).as_str())
+            .collect();
+        names.sort();
 
-        results
+        names
     }
 }
 
@@ -127,9 +59,8 @@ impl Graph {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_bron_kerbosch() {
-        let graph = Graph::from(
+    fn network() -> Network {
+        Network::from(
             r#"
 1-5
 1-2
@@ -140,21 +71,36 @@ mod tests {
 6-4
         "#
             .trim(),
-        );
+        )
+    }
 
-        let r = graph.bron_kerbosch();
+    #[test]
+    fn test_max_clique() {
+        assert_eq!(network().max_clique(), vec!["1", "2", "5"]);
+    }
 
-        let mut cliques: Vec<Vec<String>> = r
-            .iter()
-            .map(|s| s.iter().map(|s| s.to_string()).collect())
-            .collect();
+    #[test]
+    fn test_export() {
+        let dot = network().to_dot();
 
-        cliques.iter_mut().for_each(|set| set.sort());
+        assert_eq!(dot.matches("fillcolor=\"red\"").count(), 3);
+        assert!(dot.contains("n0 [label=\"1\", style=filled, fillcolor=\"red\"];"));
+        assert!(dot.contains("n4 [label=\"4\"];"));
+        assert_eq!(network().to_mermaid().matches(" --- ").count(), 7);
+    }
 
-        assert!(cliques.contains(&vec!["2".to_string(), "3".to_string()]));
-        assert!(cliques.contains(&vec!["3".to_string(), "4".to_string()]));
-        assert!(cliques.contains(&vec!["4".to_string(), "5".to_string()]));
-        assert!(cliques.contains(&vec!["4".to_string(), "6".to_string()]));
-        assert!(cliques.contains(&vec!["1".to_string(), "2".to_string(), "5".to_string()]));
+    #[test]
+    fn test_cliques_of_size() {
+        let network = network();
+
+        assert_eq!(network.cliques_of_size(2).count(), 7);
+        assert_eq!(
+            network
+                .cliques_of_size(2)
+                .containing(|name| name == "4")
+                .map(|clique| network.names(&clique))
+                .collect::<Vec<_>>(),
+            vec![vec!["4", "5"], vec!["3", "4"], vec!["4", "6"]]
+        );
     }
 }
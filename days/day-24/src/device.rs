@@ -1,95 +1,180 @@
 use std::{
-    collections::{BTreeSet, HashMap, HashSet},
-    rc::Rc,
+    collections::{HashMap, HashSet},
+    fmt::Display,
 };
 
-use itertools::Itertools;
-use lib::debugln;
+use lib::{
+    debugln,
+    export::ExportOptions,
+    graph::{Graph, NodeId},
+    Interner, Symbol,
+};
+
+mod adder;
+mod analysis;
+mod compiled;
+mod netlist;
+mod repair;
+mod spec;
+
+pub use spec::Spec;
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum GateType {
     And,
     Or,
     Xor,
+    Nand,
+    Nor,
+    Xnor,
+    Not,
+    // No input, written `0 -> w` or `1 -> w`
+    Const(bool),
 }
 
-#[derive(PartialEq, Eq, Hash, Debug)]
+// Gates written with a keyword
+const KEYWORD_GATES: [GateType; 7] = [
+    GateType::And,
+    GateType::Or,
+    GateType::Xor,
+    GateType::Nand,
+    GateType::Nor,
+    GateType::Xnor,
+    GateType::Not,
+];
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub struct Gate {
-    input: (Rc<String>, Rc<String>),
+    // As many as the gate type's arity
+    inputs: Vec<Symbol>,
     gate_type: GateType,
-    output: Rc<String>,
+    output: Symbol,
 }
 
-#[derive(Clone)]
-pub struct Device {
-    wires: BTreeSet<Rc<String>>,
-    gates: HashSet<Rc<Gate>>,
-    state: HashMap<Rc<String>, Option<bool>>,
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    // 1-based
+    pub line: usize,
+    pub message: String,
+}
 
-    input_index: HashMap<Rc<String>, Vec<Rc<Gate>>>,
-    output_index: HashMap<Rc<String>, Rc<Gate>>,
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
 }
 
-impl Device {
-    pub fn parse(input: &str) -> Self {
-        let (initial_state, gates) = input.split_once("\n\n").unwrap();
+impl std::error::Error for ParseError {}
 
-        let mut all_wires = BTreeSet::new();
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConvergeError {
+    // Wires of the loop, each one driving the next and the last one driving the first
+    Loop(Vec<String>),
+    // Input wire without a value
+    Undefined(String),
+}
 
-        let state = initial_state
-            .lines()
-            .map(|line| {
-                let (gate, value_str) = line.split_once(": ").unwrap();
+impl Display for ConvergeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConvergeError::Loop(wires) => write!(f, "loop {} -> {}", wires.join(" -> "), wires[0]),
+            ConvergeError::Undefined(wire) => write!(f, "undefined wire {}", wire),
+        }
+    }
+}
+
+impl std::error::Error for ConvergeError {}
+
+#[derive(Clone)]
+pub struct Device {
+    names: Interner,
+    // Sorted by name
+    wires: Vec<Symbol>,
+    gates: HashSet<Gate>,
+    state: HashMap<Symbol, Option<bool>>,
+
+    input_index: HashMap<Symbol, Vec<Gate>>,
+    output_index: HashMap<Symbol, Gate>,
+}
 
-                let gate = Rc::new(gate.to_string());
+impl Device {
+    // Initial values (0 or 1), a blank line, then the gates. Err with the first invalid line,
+    // including a wire given a value or driven more than once.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut names = Interner::new();
+        let mut state = HashMap::new();
+        let mut parsed_gates = HashSet::new();
+        let mut driven = HashSet::new();
+        let mut in_gates = false;
+
+        for (i, line) in input.lines().enumerate() {
+            let error = |message: String| ParseError {
+                line: i + 1,
+                message,
+            };
+
+            if !in_gates {
+                if line.is_empty() {
+                    in_gates = true;
+                } else {
+                    let (wire, value_str) = line
+                        .split_once(": ")
+                        .ok_or_else(|| error(format!("Invalid initial value {:?}", line)))?;
+
+                    let value = match value_str {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(error(format!("Invalid value {:?}", value_str))),
+                    };
+
+                    if state.insert(names.intern(wire), Some(value)).is_some() {
+                        return Err(error(format!("Duplicate value for {}", wire)));
+                    }
+                }
 
-                all_wires.insert(gate.clone());
+                continue;
+            }
 
-                (gate.clone(), value_str.parse::<u32>().map(|v| v == 1).ok())
-            })
-            .collect();
+            let (expression, output) = line
+                .split_once(" -> ")
+                .ok_or_else(|| error(format!("Invalid gate {:?}", line)))?;
 
-        let parsed_gates = gates
-            .lines()
-            .map(|line| {
-                let parts: Vec<&str> = line.split(' ').collect();
+            let parts: Vec<&str> = expression.split(' ').collect();
 
-                debug_assert!(parts.len() == 5);
+            let gate_type = |keyword: &str, arity: usize| {
+                KEYWORD_GATES
+                    .into_iter()
+                    .find(|gate_type| gate_type.keyword() == keyword && gate_type.arity() == arity)
+                    .ok_or_else(|| error(format!("Unsupported gate type {:?}", keyword)))
+            };
 
-                let a = Rc::new(parts[0].to_string());
-                let b = Rc::new(parts[2].to_string());
-                let output = Rc::new(parts[4].to_string());
-                let gate_type = parts[1];
+            let (gate_type, inputs) = match parts.as_slice() {
+                ["0"] => (GateType::Const(false), vec![]),
+                ["1"] => (GateType::Const(true), vec![]),
+                [keyword, a] => (gate_type(keyword, 1)?, vec![*a]),
+                [a, keyword, b] => (gate_type(keyword, 2)?, vec![*a, *b]),
+                _ => return Err(error(format!("Invalid gate {:?}", line))),
+            };
 
-                if !all_wires.contains(&a) {
-                    all_wires.insert(a.clone());
-                }
+            let output_symbol = names.intern(output);
 
-                if !all_wires.contains(&b) {
-                    all_wires.insert(b.clone());
-                }
+            if state.contains_key(&output_symbol) || !driven.insert(output_symbol) {
+                return Err(error(format!("{} is already driven", output)));
+            }
 
-                if !all_wires.contains(&output) {
-                    all_wires.insert(output.clone());
-                }
+            parsed_gates.insert(Gate {
+                gate_type,
+                inputs: inputs.into_iter().map(|name| names.intern(name)).collect(),
+                output: output_symbol,
+            });
+        }
 
-                let parsed_gate_type = match gate_type {
-                    "AND" => GateType::And,
-                    "OR" => GateType::Or,
-                    "XOR" => GateType::Xor,
-                    _ => panic!("Unsupported gate type"),
-                };
-
-                Rc::new(Gate {
-                    gate_type: parsed_gate_type,
-                    input: (a.clone(), b.clone()),
-                    output: output.clone(),
-                })
-            })
-            .collect();
+        let mut wires: Vec<Symbol> = names.iter().map(|(symbol, _)| symbol).collect();
+        wires.sort_by_key(|w| &names[*w]);
 
         let mut device = Device {
-            wires: all_wires,
+            names,
+            wires,
             gates: parsed_gates,
             state,
             input_index: HashMap::new(),
@@ -98,47 +183,44 @@ impl Device {
 
         device.build_indexes();
 
-        device
+        Ok(device)
     }
 
     fn build_indexes(&mut self) {
         self.input_index.clear();
         self.output_index.clear();
 
-        for gate in self.gates.iter().collect::<Vec<&Rc<Gate>>>() {
-            self.input_index
-                .entry(gate.input.0.clone())
-                .or_default()
-                .push(gate.clone());
-
-            self.input_index
-                .entry(gate.input.1.clone())
-                .or_default()
-                .push(gate.clone());
+        for gate in self.gates.iter() {
+            for input in gate.inputs.iter() {
+                self.input_index
+                    .entry(*input)
+                    .or_default()
+                    .push(gate.clone());
+            }
 
-            self.output_index.insert(gate.output.clone(), gate.clone());
+            self.output_index.insert(gate.output, gate.clone());
         }
     }
 
-    pub fn converge(&mut self) -> Result<(), ()> {
-        let z_wires: Vec<Rc<String>> = self
+    pub fn converge(&mut self) -> Result<(), ConvergeError> {
+        let z_wires: Vec<Symbol> = self
             .wires
             .iter()
-            .filter(|w| w.starts_with("z"))
-            .cloned()
+            .filter(|w| self.names[**w].starts_with("z"))
+            .copied()
             .collect();
 
         let mut visited = HashSet::new();
         for wire in z_wires {
             if self.resolve(wire, &mut visited).is_none() {
-                return Err(());
+                return Err(self.converge_error(wire));
             }
         }
 
         Ok(())
     }
 
-    fn resolve(&mut self, wire: Rc<String>, visited: &mut HashSet<Rc<String>>) -> Option<bool> {
+    fn resolve(&mut self, wire: Symbol, visited: &mut HashSet<Symbol>) -> Option<bool> {
         // debugln!("Resolving {}", wire);
 
         if let Some(value) = self.state.get(&wire) {
@@ -154,268 +236,125 @@ impl Device {
             return None;
         }
 
-        visited.insert(wire.clone());
+        visited.insert(wire);
 
-        if let Some(gate) = self.output_index.get(&wire) {
-            let gate = gate.clone();
+        if let Some(gate) = self.output_index.get(&wire).cloned() {
+            // debugln!("\t* {:?} {:?}", gate.gate_type, gate.inputs);
 
-            // debugln!("\t* {} {:?} {}", gate.input.0, gate.gate_type, gate.input.1);
+            let inputs: Option<Vec<bool>> = gate
+                .inputs
+                .iter()
+                .map(|input| self.resolve(*input, visited))
+                .collect();
 
-            if let (Some(a), Some(b)) = (
-                self.resolve(gate.input.0.clone(), visited),
-                self.resolve(gate.input.1.clone(), visited),
-            ) {
-                let output_value = gate.process(a, b);
+            if let Some(inputs) = inputs {
+                let output_value = gate.process(&inputs);
 
                 // debugln!("\t -> {}", output_value);
 
-                self.state.insert(gate.output.clone(), Some(output_value));
+                self.state.insert(gate.output, Some(output_value));
 
                 Some(output_value)
             } else {
                 // This happen in case of loop, so we need to exit fast
-                self.state.insert(gate.output.clone(), None);
+                self.state.insert(gate.output, None);
                 None
             }
         } else {
-            panic!("Impossible case !");
+            // Read by a gate but neither declared nor driven
+            None
         }
     }
 
-    pub fn solve_switch(&self, n_pairs: usize, expected_value: u64) -> Vec<Rc<String>> {
-        debugln!("Starting solve...");
-
-        let wrong_gates = self.find_wrong_gates();
-
-        debugln!(
-            "Found {} gates breaking ripple carry adder rule:",
-            wrong_gates.len()
-        );
-
-        wrong_gates.iter().for_each(|g| debugln!("{:?}", g));
-
-        if wrong_gates.len() < n_pairs * 2 {
-            debugln!("Not enough wrong gate to try permutations...");
-            return vec![];
-        }
-
-        debugln!("Trying permutation...");
-        for permutation in wrong_gates.iter().permutations(n_pairs * 2) {
-            let outputs = permutation.iter().map(|g| g.output.clone()).collect();
-
-            let mut device = self.clone();
-
-            for chunk in permutation.chunks_exact(2) {
-                device.swap_gates_output(chunk[0], chunk[1]);
-            }
-
-            device.build_indexes();
-
-            if device.converge().is_err() {
-                // debugln!("Invalid (loop)");
-                continue;
-            }
-
-            debugln!(
-                "{} == {} ({:?})",
-                expected_value,
-                device.wires_number('z'),
-                outputs
-            );
+    // Gates flagged by the adder verifier, all of them if the device isn't close to an adder
+    fn find_wrong_gates(&self) -> Vec<Gate> {
+        let mut gates: Vec<Gate> = match self.verify_adder() {
+            Some(report) if !report.is_incomplete() => report
+                .suspects()
+                .into_iter()
+                .filter_map(|wire| self.output_index.get(&self.names.get(wire)?))
+                .cloned()
+                .collect(),
+            _ => self.gates.iter().cloned().collect(),
+        };
 
-            if expected_value == device.wires_number('z') {
-                debugln!("Correct permutation !");
-                return outputs;
-            }
-        }
+        gates.sort_by_key(|gate| &self.names[gate.output]);
 
-        vec![]
+        gates
     }
 
-    fn find_wrong_gates(&self) -> HashSet<Rc<Gate>> {
-        let mut wrong_gates = HashSet::new();
-
-        for gate in self.gates.iter() {
-            let inputs_on_x_y =
-                gate.input.0.starts_with(['x', 'y']) && gate.input.1.starts_with(['x', 'y']);
-
-            let output_on_z = gate.output.starts_with('z');
-
-            // First Z gate (half adder)
-            if *gate.output == "z00" {
-                if gate.gate_type != GateType::Xor
-                    || !["x00", "y00"].contains(&gate.input.0.as_str())
-                    || !["x00", "y00"].contains(&gate.input.1.as_str())
-                {
-                    wrong_gates.insert(gate.clone());
-                }
-                continue;
-            }
-
-            // Last Z bit is carry detection
-            if *gate.output == "z45" {
-                if gate.gate_type != GateType::Or || inputs_on_x_y {
-                    wrong_gates.insert(gate.clone());
-                }
-
-                continue;
-            }
-
-            if inputs_on_x_y
-                && gate.input.0.ends_with("00")
-                && gate.input.1.ends_with("00")
-                && gate.gate_type == GateType::And
-            {
-                if gate.output.starts_with('z') {
-                    wrong_gates.insert(gate.clone());
-                }
-
-                continue;
-            }
+    // Most significant first, None if one of the wires has no value yet
+    pub fn wires_values(&self, start: char) -> Option<Vec<u8>> {
+        self.wires
+            .iter()
+            .filter(|w| self.names[**w].starts_with(start))
+            .rev()
+            .map(|w| self.state.get(w).copied().flatten().map(u8::from))
+            .collect()
+    }
 
-            // Short-circuit
-            // Xor gate not connected to X/Y must be connected to Z
-            if gate.gate_type == GateType::Xor && !inputs_on_x_y {
-                if !gate.output.starts_with('z') {
-                    wrong_gates.insert(gate.clone());
-                }
+    pub fn convert_values_to_number(values: &[u8]) -> u64 {
+        values.iter().fold(0, |acc, n| (acc << 1) + *n as u64)
+    }
 
-                continue;
-            }
+    pub fn wires_number(&self, start: char) -> Option<u64> {
+        self.wires_values(start)
+            .map(|values| Self::convert_values_to_number(&values))
+    }
 
-            // If output is on Z, Gate must be XOR and must not have input on X/Y
-            if output_on_z && (gate.gate_type != GateType::Xor || inputs_on_x_y) {
-                wrong_gates.insert(gate.clone());
-            }
+    // Wires as nodes, each gate adding an edge from each of its inputs to its output
+    pub fn to_graph(&self) -> Graph<String, GateType> {
+        let mut graph = Graph::directed();
 
-            // If Input is on X/Y with Xor gate, output must not be on Z
-            // If input is on X/Y with And gate (Carry), output must not be on Z
-            if inputs_on_x_y
-                && (gate.gate_type == GateType::Xor || gate.gate_type == GateType::And)
-                && output_on_z
-            {
-                wrong_gates.insert(gate.clone());
-            }
+        for wire in self.wires.iter() {
+            graph.add_node(self.names[*wire].to_string());
+        }
 
-            // And carry gate (not connected to X/Y) must not be on Z
-            // Or carry gate must not be on Z
-            if !inputs_on_x_y
-                && (gate.gate_type == GateType::And || gate.gate_type == GateType::Or)
-                && output_on_z
-            {
-                wrong_gates.insert(gate.clone());
-            }
+        let mut gates: Vec<&Gate> = self.gates.iter().collect();
+        gates.sort_by_key(|g| &self.names[g.output]);
 
-            if inputs_on_x_y && gate.gate_type == GateType::Xor {
-                // We check that we connect to Xor & And gates
-                if let Some(following_gates) = self.input_index.get(&gate.output) {
-                    // If we don't have 2 Gates (Xor & And)
-                    if following_gates.len() != 2
-                        || following_gates
-                            .iter()
-                            .filter(|g| g.gate_type == GateType::Xor)
-                            .count()
-                            != 1
-                        || following_gates
-                            .iter()
-                            .filter(|g| g.gate_type == GateType::And)
-                            .count()
-                            != 1
-                    {
-                        debugln!("({}) XOR gate not connected to Xor/And gate", gate.output);
-                        wrong_gates.insert(gate.clone());
-                    }
-                } else {
-                    wrong_gates.insert(gate.clone());
-                }
-            }
+        for gate in gates {
+            let output = self.node_id(&graph, gate.output);
 
-            if gate.gate_type == GateType::And {
-                // We check that we connect to Xor & And gates
-                if let Some(following_gates) = self.input_index.get(&gate.output) {
-                    // If we don't have 2 Gates (Xor & And)
-                    if following_gates.len() != 1
-                        || following_gates
-                            .iter()
-                            .filter(|g| g.gate_type == GateType::Or)
-                            .count()
-                            != 1
-                    {
-                        debugln!("({}) AND gate not connected to Or gate", gate.output);
-                        wrong_gates.insert(gate.clone());
-                    }
-                } else {
-                    wrong_gates.insert(gate.clone());
-                }
-            }
-
-            // Only match carry gate because we continue on z45
-            if gate.gate_type == GateType::Or {
-                // Check that ouput is connected on a Xor Gate with Z output or And gate (on non Z
-                // output)
-                if let Some(following_gates) = self.input_index.get(&gate.output) {
-                    // If we don't have 2 Gates (Xor & And)
-                    if following_gates.len() != 2
-                        || following_gates
-                            .iter()
-                            .filter(|g| g.gate_type == GateType::Xor)
-                            .count()
-                            != 1
-                        || following_gates
-                            .iter()
-                            .filter(|g| g.gate_type == GateType::And)
-                            .count()
-                            != 1
-                    {
-                        debugln!("({}) OR gate not connected to Xor/And gate", gate.output);
-                        wrong_gates.insert(gate.clone());
-                    }
-                } else {
-                    wrong_gates.insert(gate.clone());
-                }
+            for input in gate.inputs.iter() {
+                graph.add_edge(self.node_id(&graph, *input), output, gate.gate_type);
             }
         }
 
-        wrong_gates
+        graph
     }
 
-    fn swap_gates_output(&mut self, a: &Rc<Gate>, b: &Rc<Gate>) {
-        let new_a = Gate {
-            input: a.input.clone(),
-            gate_type: a.gate_type,
-            output: b.output.clone(),
-        };
-
-        let new_b = Gate {
-            input: b.input.clone(),
-            gate_type: b.gate_type,
-            output: a.output.clone(),
-        };
+    pub fn to_dot(&self) -> String {
+        let graph = self.to_graph();
 
-        // debugln!("Switching {} <-> {}", a.output, b.output);
+        graph.to_dot(&self.export_options(&graph))
+    }
 
-        self.gates.remove(a);
-        self.gates.remove(b);
+    pub fn to_mermaid(&self) -> String {
+        let graph = self.to_graph();
 
-        self.gates.insert(Rc::new(new_a));
-        self.gates.insert(Rc::new(new_b));
+        graph.to_mermaid(&self.export_options(&graph))
     }
 
-    pub fn wires_values(&self, start: char) -> Vec<u8> {
-        self.wires
+    // Outputs of gates breaking the adder rules are highlighted
+    fn export_options(
+        &self,
+        graph: &Graph<String, GateType>,
+    ) -> ExportOptions<'static, String, GateType> {
+        let wrong_outputs: Vec<NodeId> = self
+            .find_wrong_gates()
             .iter()
-            .filter(|w| w.starts_with(start))
-            .rev()
-            .map(|w| if self.state[w].unwrap() { 1 } else { 0 })
-            .collect()
-    }
+            .map(|gate| self.node_id(graph, gate.output))
+            .collect();
 
-    pub fn convert_values_to_number(values: &[u8]) -> u64 {
-        values.iter().fold(0, |acc, n| (acc << 1) + *n as u64)
+        ExportOptions::new(|wire: &String| wire.clone())
+            .edge_label(|gate_type: &GateType| gate_type.keyword().to_string())
+            .highlight(wrong_outputs, "red")
+            .cluster_by_prefix(&["x", "y", "z"])
     }
 
-    pub fn wires_number(&self, start: char) -> u64 {
-        Self::convert_values_to_number(&self.wires_values(start))
+    fn node_id(&self, graph: &Graph<String, GateType>, wire: Symbol) -> NodeId {
+        graph.node_id(&self.names[wire].to_string()).unwrap()
     }
 
     pub fn debug(&self) {
@@ -423,21 +362,64 @@ impl Device {
 
         debugln!("=== STATE ===");
 
-        let mut keys: Vec<Rc<String>> = self.state.keys().cloned().collect();
-        keys.sort();
+        let mut keys: Vec<Symbol> = self.state.keys().copied().collect();
+        keys.sort_by_key(|k| &self.names[*k]);
 
         for key in keys {
-            debugln!("{} = {:?}", key, self.state[&key]);
+            debugln!("{} = {:?}", &self.names[key], self.state[&key]);
         }
     }
 }
 
-impl Gate {
-    pub fn process(&self, a: bool, b: bool) -> bool {
-        match self.gate_type {
+impl GateType {
+    pub fn arity(&self) -> usize {
+        match self {
+            GateType::Const(_) => 0,
+            GateType::Not => 1,
+            _ => 2,
+        }
+    }
+
+    // As written in the netlist
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            GateType::And => "AND",
+            GateType::Or => "OR",
+            GateType::Xor => "XOR",
+            GateType::Nand => "NAND",
+            GateType::Nor => "NOR",
+            GateType::Xnor => "XNOR",
+            GateType::Not => "NOT",
+            GateType::Const(false) => "0",
+            GateType::Const(true) => "1",
+        }
+    }
+
+    // Bitwise, over 64 lanes at once. Inputs past the arity are ignored.
+    pub fn evaluate(&self, a: u64, b: u64) -> u64 {
+        match self {
             GateType::And => a & b,
             GateType::Or => a | b,
             GateType::Xor => a ^ b,
+            GateType::Nand => !(a & b),
+            GateType::Nor => !(a | b),
+            GateType::Xnor => !(a ^ b),
+            GateType::Not => !a,
+            GateType::Const(value) => {
+                if *value {
+                    u64::MAX
+                } else {
+                    0
+                }
+            }
         }
     }
 }
+
+impl Gate {
+    pub fn process(&self, inputs: &[bool]) -> bool {
+        let input = |i: usize| inputs.get(i).copied().unwrap_or(false) as u64;
+
+        self.gate_type.evaluate(input(0), input(1)) & 1 == 1
+    }
+}
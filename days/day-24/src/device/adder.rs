@@ -0,0 +1,532 @@
+use std::{collections::HashMap, fmt::Display};
+
+use lib::Symbol;
+
+use super::{Device, Gate, GateType};
+
+// Sub-circuits of one bit of a ripple carry adder. Bit 0 is a half adder made of `Sum` (x XOR y)
+// and `Carry` (x AND y) only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdderPart {
+    // x XOR y
+    PartialSum,
+    // x AND y
+    Generate,
+    // Partial sum XOR carry in, drives z
+    Sum,
+    // Partial sum AND carry in
+    Propagate,
+    // Generate OR propagate, carry in of the next bit (or the last z)
+    Carry,
+    // Carry of the previous bit, as read by this bit
+    CarryIn,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdderIssue {
+    // No gate implements the part
+    Missing {
+        bit: usize,
+        part: AdderPart,
+    },
+    // The part drives `wire` while the rest of the adder expects it on `expected`
+    Miswired {
+        bit: usize,
+        part: AdderPart,
+        wire: String,
+        expected: String,
+    },
+    // The part drives `wire` but the gates of the bit don't read it
+    Unused {
+        bit: usize,
+        part: AdderPart,
+        wire: String,
+    },
+}
+
+impl AdderIssue {
+    pub fn bit(&self) -> usize {
+        match self {
+            AdderIssue::Missing { bit, .. }
+            | AdderIssue::Miswired { bit, .. }
+            | AdderIssue::Unused { bit, .. } => *bit,
+        }
+    }
+}
+
+impl Display for AdderIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdderIssue::Missing { bit, part } => write!(f, "bit {}: missing {:?}", bit, part),
+            AdderIssue::Miswired {
+                bit,
+                part,
+                wire,
+                expected,
+            } => write!(
+                f,
+                "bit {}: {:?} drives {} instead of {}",
+                bit, part, wire, expected
+            ),
+            AdderIssue::Unused { bit, part, wire } => {
+                write!(f, "bit {}: {:?} ({}) is not read", bit, part, wire)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdderReport {
+    // Number of x (and y) bits
+    pub width: usize,
+    // The last z wire is the carry of the last bit
+    pub carry_out: bool,
+    // Sorted by bit
+    pub issues: Vec<AdderIssue>,
+}
+
+impl AdderReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn bit_issues(&self, bit: usize) -> impl Iterator<Item = &AdderIssue> {
+        self.issues.iter().filter(move |issue| issue.bit() == bit)
+    }
+
+    // Parts are missing, so it's not an adder with a few outputs swapped
+    pub fn is_incomplete(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| matches!(issue, AdderIssue::Missing { .. }))
+    }
+
+    // Wires whose driving gate is wrong, each miswired part giving both ends of the swap
+    pub fn suspects(&self) -> Vec<&str> {
+        let mut wires: Vec<&str> = self
+            .issues
+            .iter()
+            .flat_map(|issue| match issue {
+                AdderIssue::Missing { .. } => vec![],
+                AdderIssue::Miswired { wire, expected, .. } => vec![wire.as_str(), expected],
+                AdderIssue::Unused { wire, .. } => vec![wire.as_str()],
+            })
+            .collect();
+
+        wires.sort();
+        wires.dedup();
+
+        wires
+    }
+}
+
+impl Device {
+    // Matches the netlist against a ripple carry adder of x and y into z, bit by bit from the
+    // LSB. The width comes from the x wires, None if the x/y/z wires can't be the inputs and
+    // output of an adder (gaps in the numbering, x and y of different widths, ...).
+    pub fn verify_adder(&self) -> Option<AdderReport> {
+        let x = self.numbered_wires('x')?;
+        let y = self.numbered_wires('y')?;
+        let z = self.numbered_wires('z')?;
+
+        let width = x.len();
+
+        if width == 0 || y.len() != width || (z.len() != width && z.len() != width + 1) {
+            return None;
+        }
+
+        let mut verifier = Verifier {
+            device: self,
+            swaps: HashMap::new(),
+            issues: vec![],
+        };
+
+        let mut carry = verifier.half_adder(x[0], y[0], z[0]);
+
+        for bit in 1..width {
+            carry = verifier.full_adder(bit, x[bit], y[bit], z[bit], carry);
+        }
+
+        let carry_out = z.len() == width + 1;
+
+        if let (true, Some(carry)) = (carry_out, carry) {
+            if carry != z[width] {
+                verifier.miswired(width - 1, AdderPart::Carry, carry, z[width]);
+            }
+        }
+
+        Some(AdderReport {
+            width,
+            carry_out,
+            issues: verifier.issues,
+        })
+    }
+
+    // Wires named `<prefix><n>`, indexed by n which must go from 0 without gaps
+    pub(super) fn numbered_wires(&self, prefix: char) -> Option<Vec<Symbol>> {
+        let mut wires: Vec<(usize, Symbol)> = vec![];
+
+        for wire in self.wires.iter() {
+            if let Some(n) = self.names[*wire].strip_prefix(prefix) {
+                wires.push((n.parse().ok()?, *wire));
+            }
+        }
+
+        wires.sort();
+
+        if wires.iter().enumerate().any(|(i, (n, _))| i != *n) {
+            return None;
+        }
+
+        Some(wires.into_iter().map(|(_, wire)| wire).collect())
+    }
+}
+
+struct Verifier<'a> {
+    device: &'a Device,
+    // Outputs found swapped, in both directions. Gates are read through it so the bits after a
+    // miswiring are checked as if it was fixed.
+    swaps: HashMap<Symbol, Symbol>,
+    issues: Vec<AdderIssue>,
+}
+
+impl<'a> Verifier<'a> {
+    // Returns the carry
+    fn half_adder(&mut self, x: Symbol, y: Symbol, z: Symbol) -> Option<Symbol> {
+        match self.gate(GateType::Xor, x, y) {
+            Some(sum) if self.output(sum) != z => {
+                self.miswired(0, AdderPart::Sum, self.output(sum), z)
+            }
+            Some(_) => {}
+            None => self.missing(0, AdderPart::Sum),
+        }
+
+        let carry = self.gate(GateType::And, x, y).map(|gate| self.output(gate));
+
+        if carry.is_none() {
+            self.missing(0, AdderPart::Carry);
+        }
+
+        carry
+    }
+
+    // Returns the carry
+    fn full_adder(
+        &mut self,
+        bit: usize,
+        x: Symbol,
+        y: Symbol,
+        z: Symbol,
+        mut carry: Option<Symbol>,
+    ) -> Option<Symbol> {
+        let mut partial = self.gate(GateType::Xor, x, y).map(|gate| self.output(gate));
+        let generate = self.gate(GateType::And, x, y).map(|gate| self.output(gate));
+
+        if partial.is_none() {
+            self.missing(bit, AdderPart::PartialSum);
+        }
+
+        if generate.is_none() {
+            self.missing(bit, AdderPart::Generate);
+        }
+
+        // Found from its inputs first, as z is the wire most likely to be swapped
+        let sum = carry
+            .and_then(|carry| self.consumer(GateType::Xor, carry))
+            .or_else(|| partial.and_then(|partial| self.consumer(GateType::Xor, partial)))
+            .or_else(|| {
+                self.driver(z)
+                    .filter(|gate| gate.gate_type == GateType::Xor && !self.is_input_gate(gate))
+            });
+
+        match sum {
+            Some(sum) => {
+                if self.output(sum) != z {
+                    self.miswired(bit, AdderPart::Sum, self.output(sum), z);
+                }
+
+                // The sum gate tells which wires the partial sum and carry in should be
+                match (reads(sum, partial), reads(sum, carry)) {
+                    (true, true) => {}
+                    (false, true) => {
+                        let actual = other_input(sum, carry.unwrap());
+
+                        if let Some(partial) = partial {
+                            self.miswired(bit, AdderPart::PartialSum, partial, actual);
+                        }
+
+                        partial = Some(actual);
+                    }
+                    (true, false) => {
+                        let actual = other_input(sum, partial.unwrap());
+
+                        if let Some(carry) = carry {
+                            self.miswired(bit, AdderPart::CarryIn, carry, actual);
+                        }
+
+                        carry = Some(actual);
+                    }
+                    (false, false) => {
+                        if let Some(partial) = partial {
+                            self.unused(bit, AdderPart::PartialSum, partial);
+                        }
+
+                        if let Some(carry) = carry {
+                            self.unused(bit, AdderPart::CarryIn, carry);
+                        }
+                    }
+                }
+            }
+            None => self.missing(bit, AdderPart::Sum),
+        }
+
+        let propagate = match (partial, carry) {
+            (Some(partial), Some(carry)) => self
+                .gate(GateType::And, partial, carry)
+                .map(|gate| self.output(gate)),
+            _ => None,
+        };
+
+        if propagate.is_none() {
+            self.missing(bit, AdderPart::Propagate);
+        }
+
+        let Some(carry_out) = propagate
+            .and_then(|propagate| self.consumer(GateType::Or, propagate))
+            .or_else(|| generate.and_then(|generate| self.consumer(GateType::Or, generate)))
+        else {
+            self.missing(bit, AdderPart::Carry);
+            return None;
+        };
+
+        match (reads(carry_out, generate), reads(carry_out, propagate)) {
+            (false, true) => {
+                if let Some(generate) = generate {
+                    let actual = other_input(carry_out, propagate.unwrap());
+                    self.miswired(bit, AdderPart::Generate, generate, actual);
+                }
+            }
+            (true, false) => {
+                if let Some(propagate) = propagate {
+                    let actual = other_input(carry_out, generate.unwrap());
+                    self.miswired(bit, AdderPart::Propagate, propagate, actual);
+                }
+            }
+            _ => {}
+        }
+
+        Some(self.output(carry_out))
+    }
+
+    // Output of the gate once the swaps found so far are fixed
+    fn output(&self, gate: &Gate) -> Symbol {
+        *self.swaps.get(&gate.output).unwrap_or(&gate.output)
+    }
+
+    fn driver(&self, wire: Symbol) -> Option<&'a Gate> {
+        let wire = *self.swaps.get(&wire).unwrap_or(&wire);
+
+        self.device.output_index.get(&wire)
+    }
+
+    // Gate of this type reading both wires
+    fn gate(&self, gate_type: GateType, a: Symbol, b: Symbol) -> Option<&'a Gate> {
+        self.consumers(gate_type, a)
+            .filter(|gate| reads(gate, Some(b)))
+            .min_by_key(|gate| &self.device.names[gate.output])
+    }
+
+    // Gate of this type reading the wire, the first one by output name if there are several
+    fn consumer(&self, gate_type: GateType, wire: Symbol) -> Option<&'a Gate> {
+        self.consumers(gate_type, wire)
+            .min_by_key(|gate| &self.device.names[gate.output])
+    }
+
+    fn consumers(&self, gate_type: GateType, wire: Symbol) -> impl Iterator<Item = &'a Gate> {
+        self.device
+            .input_index
+            .get(&wire)
+            .into_iter()
+            .flatten()
+            .filter(move |gate| gate.gate_type == gate_type)
+    }
+
+    // Reads x or y
+    fn is_input_gate(&self, gate: &Gate) -> bool {
+        gate.inputs
+            .iter()
+            .any(|wire| self.device.names[*wire].starts_with(['x', 'y']))
+    }
+
+    fn missing(&mut self, bit: usize, part: AdderPart) {
+        self.issues.push(AdderIssue::Missing { bit, part });
+    }
+
+    fn unused(&mut self, bit: usize, part: AdderPart, wire: Symbol) {
+        self.issues.push(AdderIssue::Unused {
+            bit,
+            part,
+            wire: self.device.names[wire].to_string(),
+        });
+    }
+
+    // Records the swap unless one of the wires was already swapped
+    fn miswired(&mut self, bit: usize, part: AdderPart, wire: Symbol, expected: Symbol) {
+        if self.swaps.get(&wire) == Some(&expected) {
+            return;
+        }
+
+        if !self.swaps.contains_key(&wire) && !self.swaps.contains_key(&expected) {
+            self.swaps.insert(wire, expected);
+            self.swaps.insert(expected, wire);
+        }
+
+        self.issues.push(AdderIssue::Miswired {
+            bit,
+            part,
+            wire: self.device.names[wire].to_string(),
+            expected: self.device.names[expected].to_string(),
+        });
+    }
+}
+
+fn reads(gate: &Gate, wire: Option<Symbol>) -> bool {
+    wire.is_some_and(|wire| gate.inputs.contains(&wire))
+}
+
+// Other input of a binary gate
+fn other_input(gate: &Gate, wire: Symbol) -> Symbol {
+    gate.inputs
+        .iter()
+        .find(|input| **input != wire)
+        .copied()
+        .unwrap_or(wire)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    // Ripple carry adder of `width` bits, `swaps` being applied to the gate outputs
+    pub(in crate::device) fn adder(width: usize, swaps: &[(&str, &str)]) -> Device {
+        let mut lines = vec![];
+
+        for bit in 0..width {
+            lines.push(format!("x{:02}: {}", bit, bit % 2));
+        }
+
+        for bit in 0..width {
+            lines.push(format!("y{:02}: 1", bit));
+        }
+
+        lines.push(String::new());
+
+        let mut gates = vec![
+            ("x00 XOR y00".to_string(), "z00".to_string()),
+            ("x00 AND y00".to_string(), "c00".to_string()),
+        ];
+
+        for bit in 1..width {
+            let carry = format!("c{:02}", bit - 1);
+
+            gates.extend([
+                (
+                    format!("x{:02} XOR y{:02}", bit, bit),
+                    format!("s{:02}", bit),
+                ),
+                (
+                    format!("y{:02} AND x{:02}", bit, bit),
+                    format!("g{:02}", bit),
+                ),
+                (format!("{} XOR s{:02}", carry, bit), format!("z{:02}", bit)),
+                (format!("s{:02} AND {}", bit, carry), format!("p{:02}", bit)),
+                (
+                    format!("g{:02} OR p{:02}", bit, bit),
+                    format!("c{:02}", bit),
+                ),
+            ]);
+        }
+
+        let last = format!("c{:02}", width - 1);
+
+        for (gate, output) in gates {
+            let output = if output == last {
+                format!("z{:02}", width)
+            } else {
+                output
+            };
+
+            let output = swaps
+                .iter()
+                .find_map(|(a, b)| match output.as_str() {
+                    o if o == *a => Some(b.to_string()),
+                    o if o == *b => Some(a.to_string()),
+                    _ => None,
+                })
+                .unwrap_or(output);
+
+            lines.push(format!("{} -> {}", gate, output));
+        }
+
+        Device::parse(&lines.join("\n")).unwrap()
+    }
+
+    #[test]
+    fn test_valid_adder() {
+        for width in [1, 2, 5, 12] {
+            let report = adder(width, &[]).verify_adder().unwrap();
+
+            assert_eq!(report.width, width);
+            assert!(report.carry_out);
+            assert!(report.is_valid(), "{:?}", report.issues);
+        }
+    }
+
+    #[test]
+    fn test_swaps() {
+        let report = adder(8, &[("s03", "g03"), ("z05", "c05"), ("p06", "z07")])
+            .verify_adder()
+            .unwrap();
+
+        assert_eq!(
+            report
+                .issues
+                .iter()
+                .map(|issue| issue.to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "bit 3: PartialSum drives g03 instead of s03",
+                "bit 5: Sum drives c05 instead of z05",
+                "bit 6: Propagate drives z07 instead of p06",
+            ]
+        );
+        assert_eq!(
+            report.suspects(),
+            vec!["c05", "g03", "p06", "s03", "z05", "z07"]
+        );
+        assert_eq!(report.bit_issues(5).count(), 1);
+
+        // Last carry swapped with the first sum
+        let report = adder(4, &[("z00", "z04")]).verify_adder().unwrap();
+
+        assert_eq!(report.suspects(), vec!["z00", "z04"]);
+    }
+
+    #[test]
+    fn test_not_an_adder() {
+        assert_eq!(adder(4, &[]).numbered_wires('w'), Some(vec![]));
+        assert!(
+            Device::parse("x00: 1\nx02: 1\ny00: 0\ny01: 1\n\nx00 AND y00 -> z00")
+                .unwrap()
+                .verify_adder()
+                .is_none()
+        );
+        assert!(
+            Device::parse("x00: 1\ny00: 0\n\nx00 AND y00 -> z00\nx00 OR y00 -> z01")
+                .unwrap()
+                .verify_adder()
+                .unwrap()
+                .is_incomplete()
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use lib::{
+    graph::{Graph, NodeId},
+    Symbol,
+};
+
+use super::{ConvergeError, Device, GateType};
+
+type WireGraph = Graph<String, GateType>;
+
+// Depth of each wire and the input it gets it from
+type Levels = (Vec<usize>, Vec<Option<NodeId>>);
+
+impl Device {
+    // All the wire names, sorted
+    pub fn wires(&self) -> Vec<&str> {
+        self.wires.iter().map(|wire| &self.names[*wire]).collect()
+    }
+
+    // Wires `wire` depends on, directly or not, sorted. It only contains `wire` itself if it's in
+    // a loop. None for an unknown wire.
+    pub fn fan_in(&self, wire: &str) -> Option<Vec<String>> {
+        let graph = self.to_graph();
+        let id = graph.node_id(&wire.to_string())?;
+
+        Some(names(&graph, &upstream(&graph, &[id])))
+    }
+
+    // Outputs of the gates reading `wire`, sorted. None for an unknown wire.
+    pub fn fan_out(&self, wire: &str) -> Option<Vec<String>> {
+        let graph = self.to_graph();
+        let id = graph.node_id(&wire.to_string())?;
+
+        let mut outputs: Vec<NodeId> = graph.neighboors(id).collect();
+        outputs.sort();
+        outputs.dedup();
+
+        Some(names(&graph, &outputs))
+    }
+
+    // Gates on the longest path from an input to each output (as written by `to_verilog`), by
+    // output name. Err with one of the loops if there's any.
+    pub fn depths(&self) -> Result<Vec<(String, usize)>, Vec<String>> {
+        let graph = self.to_graph();
+        let (depth, _) = levels(&graph)?;
+
+        Ok(self
+            .outputs()
+            .into_iter()
+            .map(|wire| {
+                let id = self.node_id(&graph, wire);
+
+                (graph.node(id).clone(), depth[id.index()])
+            })
+            .collect())
+    }
+
+    // Wires of the longest path from an input to an output, ties going to the first wires by
+    // name. Err with one of the loops if there's any.
+    pub fn critical_path(&self) -> Result<Vec<String>, Vec<String>> {
+        let graph = self.to_graph();
+        let (depth, from) = levels(&graph)?;
+
+        let Some(mut current) = self
+            .outputs()
+            .into_iter()
+            .map(|wire| self.node_id(&graph, wire))
+            .min_by_key(|id| (Reverse(depth[id.index()]), *id))
+        else {
+            return Ok(vec![]);
+        };
+
+        let mut path = vec![current];
+
+        while let Some(input) = from[current.index()] {
+            path.push(input);
+            current = input;
+        }
+
+        path.reverse();
+
+        Ok(names(&graph, &path))
+    }
+
+    // One cycle per set of wires depending on each other, each wire driving the next one and the
+    // last one driving the first. Each cycle starts with its first wire by name.
+    pub fn loops(&self) -> Vec<Vec<String>> {
+        let graph = self.to_graph();
+
+        let mut loops: Vec<Vec<String>> = graph
+            .strongly_connected_components()
+            .into_iter()
+            .filter(|component| component.len() > 1 || graph.has_edge(component[0], component[0]))
+            .map(|component| {
+                let members: HashSet<NodeId> = component.iter().copied().collect();
+
+                // Every wire of the component drives another one of it, so walking them ends up
+                // in a cycle
+                let mut position = HashMap::new();
+                let mut path = vec![];
+                let mut current = component[0];
+
+                while !position.contains_key(&current) {
+                    position.insert(current, path.len());
+                    path.push(current);
+
+                    current = graph
+                        .neighboors(current)
+                        .filter(|next| members.contains(next))
+                        .min()
+                        .unwrap();
+                }
+
+                let mut cycle = path.split_off(position[&current]);
+                let first = (0..cycle.len()).min_by_key(|i| cycle[*i]).unwrap();
+                cycle.rotate_left(first);
+
+                names(&graph, &cycle)
+            })
+            .collect();
+
+        loops.sort();
+
+        loops
+    }
+
+    // Outputs of the gates no z wire depends on, sorted
+    pub fn dead_gates(&self) -> Vec<String> {
+        let graph = self.to_graph();
+
+        let z: Vec<NodeId> = self
+            .wires
+            .iter()
+            .filter(|wire| self.names[**wire].starts_with('z'))
+            .map(|wire| self.node_id(&graph, *wire))
+            .collect();
+
+        let live: HashSet<NodeId> = upstream(&graph, &z).into_iter().chain(z).collect();
+
+        self.wires
+            .iter()
+            .filter(|wire| {
+                self.output_index.contains_key(wire)
+                    && !live.contains(&self.node_id(&graph, **wire))
+            })
+            .map(|wire| self.names[*wire].to_string())
+            .collect()
+    }
+
+    // Why `wire` can't be resolved: a loop it depends on, or else one of its inputs has no value
+    pub(super) fn converge_error(&self, wire: Symbol) -> ConvergeError {
+        let name = &self.names[wire];
+        let fan_in = self.fan_in(name).unwrap_or_default();
+
+        if let Some(cycle) = self
+            .loops()
+            .into_iter()
+            .find(|cycle| fan_in.contains(&cycle[0]))
+        {
+            return ConvergeError::Loop(cycle);
+        }
+
+        let undefined = fan_in.into_iter().find(|input| {
+            self.names.get(input).is_some_and(|input| {
+                !self.output_index.contains_key(&input)
+                    && self.state.get(&input).copied().flatten().is_none()
+            })
+        });
+
+        ConvergeError::Undefined(undefined.unwrap_or_else(|| name.to_string()))
+    }
+}
+
+// Wires the given ones depend on, sorted
+fn upstream(graph: &WireGraph, from: &[NodeId]) -> Vec<NodeId> {
+    let mut seen = HashSet::new();
+    let mut stack = from.to_vec();
+
+    while let Some(id) = stack.pop() {
+        for input in graph.predecessors(id) {
+            if seen.insert(input) {
+                stack.push(input);
+            }
+        }
+    }
+
+    let mut wires: Vec<NodeId> = seen.into_iter().collect();
+    wires.sort();
+
+    wires
+}
+
+// Inputs are at depth 0, each gate adding 1 to its deepest input
+fn levels(graph: &WireGraph) -> Result<Levels, Vec<String>> {
+    let order = graph.toposort().map_err(|cycle| names(graph, &cycle))?;
+
+    let mut depth = vec![0; graph.len()];
+    let mut from = vec![None; graph.len()];
+
+    for id in order {
+        // Deepest input, the first by name on ties
+        if let Some(input) = graph
+            .predecessors(id)
+            .min_by_key(|input| (Reverse(depth[input.index()]), *input))
+        {
+            depth[id.index()] = depth[input.index()] + 1;
+            from[id.index()] = Some(input);
+        }
+    }
+
+    Ok((depth, from))
+}
+
+fn names(graph: &WireGraph, ids: &[NodeId]) -> Vec<String> {
+    ids.iter().map(|id| graph.node(*id).clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::adder::tests::adder;
+
+    #[test]
+    fn test_fan_in_fan_out() {
+        let device = adder(3, &[]);
+
+        assert_eq!(
+            device.fan_in("z01").unwrap(),
+            vec!["c00", "s01", "x00", "x01", "y00", "y01"]
+        );
+        assert_eq!(device.fan_out("c00").unwrap(), vec!["p01", "z01"]);
+        assert_eq!(device.fan_out("z02").unwrap(), Vec::<String>::new());
+        assert_eq!(device.fan_in("nope"), None);
+    }
+
+    #[test]
+    fn test_depths() {
+        let device = adder(4, &[]);
+
+        assert_eq!(
+            device.depths().unwrap(),
+            vec![
+                ("z00".to_string(), 1),
+                ("z01".to_string(), 2),
+                ("z02".to_string(), 4),
+                ("z03".to_string(), 6),
+                ("z04".to_string(), 7),
+            ]
+        );
+        assert_eq!(
+            device.critical_path().unwrap(),
+            vec!["x00", "c00", "p01", "c01", "p02", "c02", "p03", "z04"]
+        );
+    }
+
+    #[test]
+    fn test_loops() {
+        let mut device = Device::parse(
+            "x00: 1\ny00: 0\n\na AND y00 -> z00\nx00 XOR z00 -> a\nb OR b -> b\nx00 OR y00 -> z01",
+        )
+        .unwrap();
+
+        assert_eq!(
+            device.loops(),
+            vec![vec!["a", "z00"], vec!["b"]]
+                .into_iter()
+                .map(|cycle| cycle.into_iter().map(String::from).collect())
+                .collect::<Vec<Vec<String>>>()
+        );
+        assert!(device.depths().is_err());
+        assert_eq!(
+            device.converge(),
+            Err(ConvergeError::Loop(vec![
+                "a".to_string(),
+                "z00".to_string()
+            ]))
+        );
+        assert_eq!(device.dead_gates(), vec!["b"]);
+        assert!(adder(4, &[]).loops().is_empty());
+
+        assert_eq!(
+            Device::parse("x00: 0\ny00: 0\n\nx00 XOR q -> z00")
+                .unwrap()
+                .converge(),
+            Err(ConvergeError::Undefined("q".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_dead_gates() {
+        let device =
+            Device::parse("x00: 1\ny00: 0\n\nx00 AND y00 -> a\na OR y00 -> b\nx00 XOR y00 -> z00")
+                .unwrap();
+
+        assert_eq!(device.dead_gates(), vec!["a", "b"]);
+        assert!(adder(4, &[]).dead_gates().is_empty());
+    }
+}
@@ -0,0 +1,245 @@
+use lib::Symbol;
+
+use super::{Device, Gate, GateType};
+
+// Input vectors evaluated per pass, one per bit of the lanes
+const LANES: usize = u64::BITS as usize;
+
+#[derive(Debug, Clone, Copy)]
+struct Op {
+    gate_type: GateType,
+    a: usize,
+    b: usize,
+    output: usize,
+}
+
+// Gates in topological order over wire slots (the wire symbol indexes), every wire holding one
+// bit per input vector
+#[derive(Debug, Clone)]
+pub struct CompiledDevice {
+    ops: Vec<Op>,
+    x: Vec<usize>,
+    y: Vec<usize>,
+    z: Vec<usize>,
+    n_wires: usize,
+    // Gates in a loop (or depending on one) are left out, their outputs staying 0
+    looped: usize,
+}
+
+impl Device {
+    // None if x/y/z aren't numbered wires of at most 64 bits
+    pub fn compile(&self) -> Option<CompiledDevice> {
+        let x = self.numbered_wires('x')?;
+        let y = self.numbered_wires('y')?;
+        let z = self.numbered_wires('z')?;
+
+        if [&x, &y, &z]
+            .iter()
+            .any(|wires| wires.len() > u64::BITS as usize)
+        {
+            return None;
+        }
+
+        let mut gates: Vec<Gate> = self.gates.iter().cloned().collect();
+        gates.sort_by_key(|gate| &self.names[gate.output]);
+
+        Some(CompiledDevice::new(&gates, &x, &y, &z, self.names.len()))
+    }
+}
+
+impl CompiledDevice {
+    pub(super) fn new(
+        gates: &[Gate],
+        x: &[Symbol],
+        y: &[Symbol],
+        z: &[Symbol],
+        n_wires: usize,
+    ) -> Self {
+        let order = topological_order(gates, n_wires);
+        let slots = |wires: &[Symbol]| wires.iter().map(|wire| wire.index()).collect();
+
+        CompiledDevice {
+            looped: gates.len() - order.len(),
+            ops: order
+                .into_iter()
+                .map(|gate| {
+                    // Unused inputs of unary and constant gates read slot 0, ignored anyway
+                    let a = gate.inputs.first().map_or(0, |input| input.index());
+
+                    Op {
+                        gate_type: gate.gate_type,
+                        a,
+                        b: gate.inputs.get(1).map_or(a, |input| input.index()),
+                        output: gate.output.index(),
+                    }
+                })
+                .collect(),
+            x: slots(x),
+            y: slots(y),
+            z: slots(z),
+            n_wires,
+        }
+    }
+
+    pub fn looped(&self) -> usize {
+        self.looped
+    }
+
+    // z for each (x[i], y[i]), 64 vectors at a time
+    pub fn evaluate_batch(&self, x: &[u64], y: &[u64]) -> Vec<u64> {
+        assert_eq!(x.len(), y.len(), "As many x as y values");
+
+        let mut lanes = vec![0; self.n_wires];
+        let mut z = Vec::with_capacity(x.len());
+
+        for (x, y) in x.chunks(LANES).zip(y.chunks(LANES)) {
+            self.evaluate_into(
+                &transpose(x, self.x.len()),
+                &transpose(y, self.y.len()),
+                &mut lanes,
+            );
+
+            let z_lanes: Vec<u64> = self.z.iter().map(|wire| lanes[*wire]).collect();
+
+            z.extend(transpose(&z_lanes, x.len()));
+        }
+
+        z
+    }
+
+    // Same with the vectors already as lanes, x[bit] holding that bit of up to 64 vectors (one
+    // per bit of the word). Returns the z lanes.
+    pub fn evaluate_lanes(&self, x: &[u64], y: &[u64]) -> Vec<u64> {
+        let mut lanes = vec![0; self.n_wires];
+
+        self.evaluate_into(x, y, &mut lanes);
+
+        self.z.iter().map(|wire| lanes[*wire]).collect()
+    }
+
+    fn evaluate_into(&self, x: &[u64], y: &[u64], lanes: &mut [u64]) {
+        lanes.fill(0);
+
+        for (wire, lane) in self.x.iter().zip(x).chain(self.y.iter().zip(y)) {
+            lanes[*wire] = *lane;
+        }
+
+        for op in self.ops.iter() {
+            lanes[op.output] = op.gate_type.evaluate(lanes[op.a], lanes[op.b]);
+        }
+    }
+}
+
+// Bit matrix transposition: bit j of result[i] is bit i of values[j], for `bits` results
+pub(super) fn transpose(values: &[u64], bits: usize) -> Vec<u64> {
+    (0..bits)
+        .map(|bit| {
+            values
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (lane, value)| acc | (value >> bit & 1) << lane)
+        })
+        .collect()
+}
+
+// Gates ordered so each one comes after the gates driving its inputs. Gates in a loop, or
+// depending on one, are left out.
+fn topological_order(gates: &[Gate], n_wires: usize) -> Vec<&Gate> {
+    let mut driven = vec![false; n_wires];
+
+    // Gates reading each wire, flattened: consumers[start[w]..start[w + 1]] read wire w
+    let mut start = vec![0; n_wires + 1];
+
+    for gate in gates.iter() {
+        driven[gate.output.index()] = true;
+
+        for input in gate.inputs.iter() {
+            start[input.index() + 1] += 1;
+        }
+    }
+
+    for wire in 0..n_wires {
+        start[wire + 1] += start[wire];
+    }
+
+    let mut consumers = vec![0; start[n_wires]];
+    let mut next = start.clone();
+
+    for (i, gate) in gates.iter().enumerate() {
+        for input in gate.inputs.iter() {
+            consumers[next[input.index()]] = i;
+            next[input.index()] += 1;
+        }
+    }
+
+    let mut pending: Vec<usize> = gates
+        .iter()
+        .map(|gate| {
+            gate.inputs
+                .iter()
+                .filter(|input| driven[input.index()])
+                .count()
+        })
+        .collect();
+
+    let mut ready: Vec<usize> = (0..gates.len()).filter(|i| pending[*i] == 0).collect();
+    let mut order = Vec::with_capacity(gates.len());
+
+    while let Some(i) = ready.pop() {
+        order.push(&gates[i]);
+
+        let output = gates[i].output.index();
+
+        for &consumer in consumers[start[output]..start[output + 1]].iter() {
+            pending[consumer] -= 1;
+
+            if pending[consumer] == 0 {
+                ready.push(consumer);
+            }
+        }
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::adder::tests::adder;
+
+    #[test]
+    fn test_exhaustive() {
+        let compiled = adder(4, &[]).compile().unwrap();
+        let (x, y): (Vec<u64>, Vec<u64>) =
+            (0..16).flat_map(|x| (0..16).map(move |y| (x, y))).unzip();
+
+        let z = compiled.evaluate_batch(&x, &y);
+
+        assert_eq!(z.len(), 256);
+        assert!((0..256).all(|i| z[i] == x[i] + y[i]));
+    }
+
+    #[test]
+    fn test_same_as_converge() {
+        let device = adder(6, &[("z02", "c03"), ("s04", "g04")]);
+        let mut converged = device.clone();
+        converged.converge().unwrap();
+
+        let x = converged.wires_number('x').unwrap();
+        let y = converged.wires_number('y').unwrap();
+
+        assert_eq!(
+            device.compile().unwrap().evaluate_batch(&[x], &[y]),
+            vec![converged.wires_number('z').unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_loop() {
+        let device = Device::parse("x00: 1\ny00: 0\n\na AND y00 -> z00\nx00 XOR z00 -> a").unwrap();
+        let compiled = device.compile().unwrap();
+
+        assert_eq!(compiled.looped(), 2);
+        assert_eq!(compiled.evaluate_batch(&[1], &[1]), vec![0]);
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+
+use lib::Symbol;
+
+use super::{Device, Gate, GateType};
+
+impl Device {
+    // Puzzle format, inputs then gates sorted by name so `Device::parse` gives back the same
+    // device
+    pub fn to_netlist(&self) -> String {
+        let mut lines: Vec<String> = self
+            .inputs()
+            .into_iter()
+            .filter_map(|wire| {
+                let value = self.state.get(&wire).copied().flatten()?;
+
+                Some(format!("{}: {}", &self.names[wire], value as u8))
+            })
+            .collect();
+
+        lines.push(String::new());
+
+        for gate in self.sorted_gates() {
+            let inputs: Vec<&str> = gate.inputs.iter().map(|w| &self.names[*w]).collect();
+
+            let expression = match inputs.as_slice() {
+                [a, b] => format!("{} {} {}", a, gate.gate_type.keyword(), b),
+                [a] => format!("{} {}", gate.gate_type.keyword(), a),
+                _ => gate.gate_type.keyword().to_string(),
+            };
+
+            lines.push(format!("{} -> {}", expression, &self.names[gate.output]));
+        }
+
+        lines.join("\n")
+    }
+
+    // Structural Verilog, one primitive instance per gate
+    pub fn to_verilog(&self, module: &str) -> String {
+        let (inputs, outputs, internal) = (self.inputs(), self.outputs(), self.internal_wires());
+        let names = |wires: &[Symbol]| {
+            wires
+                .iter()
+                .map(|wire| self.names[*wire].to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        let mut lines = vec![
+            format!(
+                "module {} ({});",
+                module,
+                names(&[inputs.clone(), outputs.clone()].concat())
+            ),
+            format!("  input {};", names(&inputs)),
+            format!("  output {};", names(&outputs)),
+        ];
+
+        if !internal.is_empty() {
+            lines.push(format!("  wire {};", names(&internal)));
+        }
+
+        lines.push(String::new());
+
+        for gate in self.sorted_gates() {
+            let output = &self.names[gate.output];
+
+            lines.push(match gate.gate_type {
+                GateType::Const(value) => format!("  assign {} = 1'b{};", output, value as u8),
+                gate_type => format!(
+                    "  {} g_{} ({}, {});",
+                    gate_type.keyword().to_lowercase(),
+                    output,
+                    output,
+                    names(&gate.inputs)
+                ),
+            });
+        }
+
+        lines.push("endmodule".to_string());
+
+        lines.join("\n")
+    }
+
+    // Berkeley Logic Interchange Format, each gate as a `.names` cover of its on-set
+    pub fn to_blif(&self, model: &str) -> String {
+        let names = |wires: &[Symbol]| {
+            wires
+                .iter()
+                .map(|wire| &self.names[*wire])
+                .collect::<Vec<&str>>()
+                .join(" ")
+        };
+
+        let mut lines = vec![
+            format!(".model {}", model),
+            format!(".inputs {}", names(&self.inputs())),
+            format!(".outputs {}", names(&self.outputs())),
+        ];
+
+        for gate in self.sorted_gates() {
+            lines.push(format!(
+                ".names {}",
+                names(&[gate.inputs.clone(), vec![gate.output]].concat())
+            ));
+
+            let cover: &[&str] = match gate.gate_type {
+                GateType::And => &["11 1"],
+                GateType::Or => &["1- 1", "-1 1"],
+                GateType::Xor => &["10 1", "01 1"],
+                GateType::Nand => &["0- 1", "-0 1"],
+                GateType::Nor => &["00 1"],
+                GateType::Xnor => &["00 1", "11 1"],
+                GateType::Not => &["0 1"],
+                GateType::Const(true) => &["1"],
+                // Empty on-set
+                GateType::Const(false) => &[],
+            };
+
+            lines.extend(cover.iter().map(|row| row.to_string()));
+        }
+
+        lines.push(".end".to_string());
+
+        lines.join("\n")
+    }
+
+    fn sorted_gates(&self) -> Vec<&Gate> {
+        let mut gates: Vec<&Gate> = self.gates.iter().collect();
+        gates.sort_by_key(|gate| &self.names[gate.output]);
+
+        gates
+    }
+
+    // Wires no gate drives
+    fn inputs(&self) -> Vec<Symbol> {
+        self.wires
+            .iter()
+            .filter(|wire| !self.output_index.contains_key(wire))
+            .copied()
+            .collect()
+    }
+
+    // Driven z wires, and driven wires no gate reads
+    pub(super) fn outputs(&self) -> Vec<Symbol> {
+        self.wires
+            .iter()
+            .filter(|wire| {
+                self.output_index.contains_key(wire)
+                    && (self.names[**wire].starts_with('z') || !self.input_index.contains_key(wire))
+            })
+            .copied()
+            .collect()
+    }
+
+    fn internal_wires(&self) -> Vec<Symbol> {
+        let outputs: HashSet<Symbol> = self.outputs().into_iter().collect();
+
+        self.wires
+            .iter()
+            .filter(|wire| self.output_index.contains_key(wire) && !outputs.contains(wire))
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETLIST: &str = "\
+a: 1
+b: 0
+
+a AND b -> c
+NOT c -> d
+1 -> e
+d XNOR e -> z00
+c NOR b -> z01";
+
+    #[test]
+    fn test_round_trip() {
+        let device = Device::parse(NETLIST).unwrap();
+
+        assert_eq!(device.to_netlist(), NETLIST);
+        assert_eq!(
+            Device::parse(&device.to_netlist()).unwrap().to_netlist(),
+            NETLIST
+        );
+
+        let mut device = device;
+        device.converge().unwrap();
+
+        // c = 0, d = 1, z00 = 1 XNOR 1, z01 = 0 NOR 0
+        assert_eq!(device.wires_number('z'), Some(0b11));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let line = |input: &str| Device::parse(input).err().map(|error| error.line);
+
+        assert_eq!(line("a = 1\n\nNOT a -> z00"), Some(1));
+        assert_eq!(line("a: 1\n\nNOT a -> z00\na MUX a -> z01"), Some(4));
+        assert_eq!(line("a: 1\n\nAND a -> z00"), Some(3));
+        assert_eq!(line("a: 1\n\na AND a"), Some(3));
+        assert_eq!(line("a: 1\n\na AND a a -> z00"), Some(3));
+        // Only 0 and 1 are values
+        assert_eq!(line("a: 1\nb: 7\n\na AND b -> z00"), Some(2));
+        assert_eq!(line("a: foo\n\nNOT a -> z00"), Some(1));
+        assert_eq!(line("a: 1\na: 0\n\nNOT a -> z00"), Some(2));
+        // Wires have a single driver
+        assert_eq!(line("a: 1\n\nNOT a -> z00\na AND a -> z00"), Some(4));
+        assert_eq!(line("a: 1\n\nNOT a -> z00\nNOT a -> z00"), Some(4));
+        assert_eq!(line("a: 1\n\nNOT z00 -> a"), Some(3));
+    }
+
+    #[test]
+    fn test_verilog() {
+        assert_eq!(
+            Device::parse(NETLIST).unwrap().to_verilog("example"),
+            "\
+module example (a, b, z00, z01);
+  input a, b;
+  output z00, z01;
+  wire c, d, e;
+
+  and g_c (c, a, b);
+  not g_d (d, c);
+  assign e = 1'b1;
+  xnor g_z00 (z00, d, e);
+  nor g_z01 (z01, c, b);
+endmodule"
+        );
+    }
+
+    #[test]
+    fn test_blif() {
+        assert_eq!(
+            Device::parse(NETLIST).unwrap().to_blif("example"),
+            "\
+.model example
+.inputs a b
+.outputs z00 z01
+.names a b c
+11 1
+.names c d
+0 1
+.names e
+1
+.names d e z00
+00 1
+11 1
+.names c b z01
+00 1
+.end"
+        );
+    }
+}
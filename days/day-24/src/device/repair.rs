@@ -0,0 +1,507 @@
+use std::collections::{HashMap, HashSet};
+
+use lib::{debugln, Symbol};
+
+use super::{
+    compiled::{transpose, CompiledDevice},
+    spec::Spec,
+    Device, Gate,
+};
+
+// Pseudo random x/y pairs simulated on top of the carry chain patterns
+const RANDOM_VECTORS: usize = 128;
+
+// Pairs of swapped outputs
+type Swaps = Vec<(Symbol, Symbol)>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    // Swapped output wires, sorted within each pair and by pair
+    pub swaps: Vec<(String, String)>,
+    pub evidence: Evidence,
+}
+
+impl Repair {
+    // All the swapped wires, sorted
+    pub fn wires(&self) -> Vec<&str> {
+        let mut wires: Vec<&str> = self
+            .swaps
+            .iter()
+            .flat_map(|(a, b)| [a.as_str(), b.as_str()])
+            .collect();
+
+        wires.sort();
+
+        wires
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evidence {
+    // Lowest failing bit before each swap, in the order the swaps were found
+    pub fixed_bits: Vec<usize>,
+    // For each swap, an x/y pair failing at its fixed bit before the swap
+    pub counterexamples: Vec<(u64, u64)>,
+    // x/y pairs simulated on the repaired device, all of them matching the spec
+    pub vectors: usize,
+}
+
+// Lowest bit of z not matching the spec, with an x/y pair failing at it
+#[derive(Clone, Copy)]
+struct Failure {
+    bit: usize,
+    counterexample: (u64, u64),
+}
+
+enum Outcome {
+    Correct,
+    FailsAt(Failure),
+    // More gates stuck in loops than allowed
+    Loop,
+}
+
+impl Device {
+    // Smallest sets of at most `max_swaps` gate output swaps making the device match `spec`,
+    // checked by simulation. Bits are repaired from the lowest failing one upward. z of that bit
+    // only changes if a swap involves a gate it depends on, so every set of 1, then 2... such
+    // swaps (each one with any other gate) is tried until some move the lowest failing bit
+    // higher without adding gates to a loop, even if none of their swaps does on its own. Each
+    // of those repairs of the bit is then extended the same way, backtracking once `max_swaps`
+    // is reached. A bit is repaired with as few swaps as possible: larger repairs of a bit which
+    // would only pay off on higher bits aren't tried. Empty if x/y/z aren't numbered wires (of
+    // at most 64 bits).
+    pub fn repair(&self, max_swaps: usize, spec: &Spec) -> Vec<Repair> {
+        let (Some(x), Some(y), Some(z)) = (
+            self.numbered_wires('x'),
+            self.numbered_wires('y'),
+            self.numbered_wires('z'),
+        ) else {
+            return vec![];
+        };
+
+        if x.len() != y.len() || x.len() >= u64::BITS as usize || z.len() > u64::BITS as usize {
+            return vec![];
+        }
+
+        let mut gates: Vec<Gate> = self.gates.iter().cloned().collect();
+        gates.sort_by_key(|gate| &self.names[gate.output]);
+
+        let (x_values, y_values) = test_vectors(x.len());
+
+        let batches = x_values
+            .chunks(64)
+            .zip(y_values.chunks(64))
+            .map(|(x_values, y_values)| {
+                let expected: Vec<u64> = x_values
+                    .iter()
+                    .zip(y_values)
+                    .map(|(x, y)| spec.evaluate(*x, *y))
+                    .collect();
+
+                Batch {
+                    x: transpose(x_values, x.len()),
+                    y: transpose(y_values, y.len()),
+                    expected: transpose(&expected, z.len()),
+                    used: u64::MAX >> (64 - x_values.len()),
+                    vectors: x_values
+                        .iter()
+                        .copied()
+                        .zip(y_values.iter().copied())
+                        .collect(),
+                }
+            })
+            .collect();
+
+        let mut search = Search {
+            device: self,
+            batches,
+            x,
+            y,
+            z,
+            max_swaps,
+            solutions: vec![],
+            loops: 0,
+        };
+
+        let looped = search.compile(&gates).looped();
+
+        match search.check(&gates, 0, looped) {
+            Outcome::Correct => search.solutions.push((vec![], vec![])),
+            Outcome::FailsAt(failure) => {
+                search.explore(&mut gates, &mut vec![], &mut vec![], failure, looped)
+            }
+            Outcome::Loop => unreachable!(),
+        }
+
+        debugln!(
+            "{} repairs found, {} swaps pruned by loops",
+            search.solutions.len(),
+            search.loops
+        );
+
+        let Some(minimal) = search.solutions.iter().map(|(swaps, _)| swaps.len()).min() else {
+            return vec![];
+        };
+
+        let mut repairs: Vec<Repair> = search
+            .solutions
+            .iter()
+            .filter(|(swaps, _)| swaps.len() == minimal)
+            .map(|(swaps, failures)| {
+                let mut swaps: Vec<(String, String)> = swaps
+                    .iter()
+                    .map(|(a, b)| {
+                        let (a, b) = (&self.names[*a], &self.names[*b]);
+
+                        (a.min(b).to_string(), a.max(b).to_string())
+                    })
+                    .collect();
+
+                swaps.sort();
+
+                Repair {
+                    swaps,
+                    evidence: Evidence {
+                        fixed_bits: failures.iter().map(|failure| failure.bit).collect(),
+                        counterexamples: failures
+                            .iter()
+                            .map(|failure| failure.counterexample)
+                            .collect(),
+                        vectors: x_values.len(),
+                    },
+                }
+            })
+            .collect();
+
+        // The same set can be found in different orders
+        repairs.sort_by(|a, b| a.swaps.cmp(&b.swaps));
+        repairs.dedup_by(|a, b| a.swaps == b.swaps);
+
+        repairs
+    }
+}
+
+// Up to 64 vectors as lanes, see `CompiledDevice::evaluate_lanes`
+struct Batch {
+    x: Vec<u64>,
+    y: Vec<u64>,
+    // Target z lanes
+    expected: Vec<u64>,
+    // Lanes holding a vector
+    used: u64,
+    // x/y pair of each lane
+    vectors: Vec<(u64, u64)>,
+}
+
+struct Search<'a> {
+    device: &'a Device,
+    x: Vec<Symbol>,
+    y: Vec<Symbol>,
+    z: Vec<Symbol>,
+    batches: Vec<Batch>,
+    max_swaps: usize,
+    // Swapped outputs and the failure each of them was part of the repair of
+    solutions: Vec<(Swaps, Vec<Failure>)>,
+    loops: usize,
+}
+
+impl Search<'_> {
+    // `failure` is the lowest failing bit of `gates`, `looped` the number of gates in loops
+    fn explore(
+        &mut self,
+        gates: &mut [Gate],
+        swaps: &mut Swaps,
+        failures: &mut Vec<Failure>,
+        failure: Failure,
+        looped: usize,
+    ) {
+        for size in 1..=self.max_swaps - swaps.len() {
+            let mut repairs = vec![];
+
+            self.repairs_of_bit(
+                gates,
+                swaps,
+                &mut vec![],
+                size,
+                failure.bit,
+                looped,
+                &mut repairs,
+            );
+
+            if repairs.is_empty() {
+                continue;
+            }
+
+            for (repair, outcome) in repairs {
+                for (a, b) in repair.iter() {
+                    swap_wires(gates, *a, *b);
+                    swaps.push((*a, *b));
+                    failures.push(failure);
+                }
+
+                match outcome {
+                    Outcome::Correct => self.solutions.push((swaps.clone(), failures.clone())),
+                    Outcome::FailsAt(next) => {
+                        let looped = self.compile(gates).looped();
+
+                        self.explore(gates, swaps, failures, next, looped)
+                    }
+                    Outcome::Loop => unreachable!(),
+                }
+
+                for (a, b) in repair.iter().rev() {
+                    swap_wires(gates, *a, *b);
+                    swaps.pop();
+                    failures.pop();
+                }
+            }
+
+            return;
+        }
+    }
+
+    // Sets of `size` swaps (`repair` holding the ones picked so far), each one involving a gate
+    // z[bit] depends on once the previous ones are applied, moving the lowest failing bit above
+    // `bit`. Each set is pushed once, sorted, along with the outcome once applied.
+    #[allow(clippy::too_many_arguments)]
+    fn repairs_of_bit(
+        &mut self,
+        gates: &mut [Gate],
+        swaps: &Swaps,
+        repair: &mut Swaps,
+        size: usize,
+        bit: usize,
+        looped: usize,
+        repairs: &mut Vec<(Swaps, Outcome)>,
+    ) {
+        let swapped: HashSet<Symbol> = swaps
+            .iter()
+            .chain(repair.iter())
+            .flat_map(|(a, b)| [*a, *b])
+            .collect();
+        let mut tried = HashSet::new();
+
+        for i in self.cone(gates, bit) {
+            for j in 0..gates.len() {
+                if i == j
+                    || swapped.contains(&gates[i].output)
+                    || swapped.contains(&gates[j].output)
+                    || !tried.insert((i.min(j), i.max(j)))
+                {
+                    continue;
+                }
+
+                let (a, b) = (gates[i].output, gates[j].output);
+
+                repair.push((a.min(b), a.max(b)));
+                swap_outputs(gates, i, j);
+
+                if repair.len() < size {
+                    self.repairs_of_bit(gates, swaps, repair, size, bit, looped, repairs);
+                } else {
+                    let mut sorted = repair.clone();
+                    sorted.sort();
+
+                    if !repairs.iter().any(|(other, _)| *other == sorted) {
+                        match self.check(gates, bit, looped) {
+                            Outcome::FailsAt(next) if next.bit <= bit => {}
+                            Outcome::Loop => self.loops += 1,
+                            outcome => repairs.push((sorted, outcome)),
+                        }
+                    }
+                }
+
+                swap_outputs(gates, i, j);
+                repair.pop();
+            }
+        }
+    }
+
+    // Simulates every vector, stopping as soon as one fails at or below `floor`. Gates stuck in
+    // loops are left out (their outputs stay false), up to `max_looped` of them.
+    fn check(&self, gates: &[Gate], floor: usize, max_looped: usize) -> Outcome {
+        let compiled = self.compile(gates);
+
+        if compiled.looped() > max_looped {
+            return Outcome::Loop;
+        }
+
+        let mut lowest: Option<Failure> = None;
+
+        for batch in self.batches.iter() {
+            let z = compiled.evaluate_lanes(&batch.x, &batch.y);
+
+            let Some((bit, wrong)) = (0..z.len())
+                .map(|bit| (bit, (z[bit] ^ batch.expected[bit]) & batch.used))
+                .find(|(_, wrong)| *wrong != 0)
+            else {
+                continue;
+            };
+
+            if lowest.is_none_or(|lowest| bit < lowest.bit) {
+                lowest = Some(Failure {
+                    bit,
+                    counterexample: batch.vectors[wrong.trailing_zeros() as usize],
+                });
+            }
+
+            if bit <= floor {
+                break;
+            }
+        }
+
+        match lowest {
+            Some(failure) => Outcome::FailsAt(failure),
+            None => Outcome::Correct,
+        }
+    }
+
+    fn compile(&self, gates: &[Gate]) -> CompiledDevice {
+        CompiledDevice::new(gates, &self.x, &self.y, &self.z, self.device.names.len())
+    }
+
+    // Indexes of the gates z[bit] depends on
+    fn cone(&self, gates: &[Gate], bit: usize) -> Vec<usize> {
+        let drivers: HashMap<Symbol, usize> = gates
+            .iter()
+            .enumerate()
+            .map(|(i, gate)| (gate.output, i))
+            .collect();
+
+        let mut cone = vec![];
+        let mut seen = HashSet::new();
+        let mut stack = vec![self.z[bit]];
+
+        while let Some(wire) = stack.pop() {
+            if let Some(&i) = drivers.get(&wire) {
+                if seen.insert(i) {
+                    cone.push(i);
+                    stack.extend(gates[i].inputs.iter().copied());
+                }
+            }
+        }
+
+        cone.sort();
+
+        cone
+    }
+}
+
+fn swap_outputs(gates: &mut [Gate], i: usize, j: usize) {
+    let output = gates[i].output;
+
+    gates[i].output = gates[j].output;
+    gates[j].output = output;
+}
+
+// Same, by output wire
+fn swap_wires(gates: &mut [Gate], a: Symbol, b: Symbol) {
+    let i = gates.iter().position(|gate| gate.output == a).unwrap();
+    let j = gates.iter().position(|gate| gate.output == b).unwrap();
+
+    swap_outputs(gates, i, j);
+}
+
+// Carry chain stress patterns (a carry generated at each bit, rippling from the bottom to each
+// bit, through every bit) followed by pseudo random pairs
+pub(super) fn test_vectors(width: usize) -> (Vec<u64>, Vec<u64>) {
+    let mask = (1 << width) - 1;
+    let alternate = 0x5555_5555_5555_5555 & mask;
+
+    let mut vectors = vec![
+        (0, 0),
+        (mask, mask),
+        (mask, 1),
+        (1, mask),
+        (mask, 0),
+        (0, mask),
+        (alternate, !alternate & mask),
+        (alternate, alternate),
+        (!alternate & mask, !alternate & mask),
+    ];
+
+    for bit in 0..width {
+        let single = 1 << bit;
+
+        vectors.extend([
+            (single, 0),
+            (0, single),
+            (single, single),
+            (single - 1, 1),
+            (mask ^ single, single),
+        ]);
+    }
+
+    // xorshift64, fixed seed so the search is reproducible
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state & mask
+    };
+
+    vectors.extend((0..RANDOM_VECTORS).map(|_| (next(), next())));
+
+    vectors.into_iter().unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device::{adder::tests::adder, spec::Spec};
+
+    #[test]
+    fn test_repair() {
+        let repairs =
+            adder(8, &[("s03", "g03"), ("z05", "c05"), ("p06", "z07")]).repair(3, &Spec::Add);
+
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(
+            repairs[0].wires(),
+            vec!["c05", "g03", "p06", "s03", "z05", "z07"]
+        );
+        assert_eq!(repairs[0].evidence.fixed_bits, vec![3, 5, 7]);
+
+        // Each counterexample fails at its bit
+        let device = adder(8, &[("s03", "g03"), ("z05", "c05"), ("p06", "z07")]);
+        for ((x, y), bit) in repairs[0]
+            .evidence
+            .counterexamples
+            .iter()
+            .zip(repairs[0].evidence.fixed_bits.iter())
+        {
+            let z = device.compile().unwrap().evaluate_batch(&[*x], &[*y])[0];
+
+            assert_ne!((z ^ (x + y)) >> bit & 1, 0);
+        }
+
+        // Not enough swaps allowed
+        assert!(adder(8, &[("s03", "g03"), ("z05", "c05")])
+            .repair(1, &Spec::Add)
+            .is_empty());
+
+        assert_eq!(
+            adder(4, &[]).repair(2, &Spec::Add)[0].swaps,
+            Vec::<(String, String)>::new()
+        );
+    }
+
+    #[test]
+    fn test_interacting_swaps() {
+        // Both swaps break bit 3, neither of them fixes it on its own
+        let device = adder(6, &[("s03", "g03"), ("z03", "c03")]);
+        let repairs = device.repair(2, &Spec::Add);
+
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(
+            repairs[0].swaps,
+            vec![
+                ("c03".to_string(), "z03".to_string()),
+                ("g03".to_string(), "s03".to_string())
+            ]
+        );
+        assert_eq!(repairs[0].evidence.fixed_bits, vec![3, 3]);
+
+        assert!(device.repair(1, &Spec::Add).is_empty());
+    }
+}
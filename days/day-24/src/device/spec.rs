@@ -0,0 +1,185 @@
+use super::{repair::test_vectors, Device};
+
+// Up to this many bits per operand every x/y pair is checked
+const EXHAUSTIVE_BITS: usize = 6;
+
+// Intended function of the circuit, z = f(x, y) truncated to the z width
+pub enum Spec {
+    Add,
+    // Two's complement
+    Sub,
+    And,
+    Or,
+    Xor,
+    // 1 if x < y
+    Less,
+    // 1 if x == y
+    Equal,
+    Custom(Box<dyn Fn(u64, u64) -> u64>),
+}
+
+impl Spec {
+    pub fn evaluate(&self, x: u64, y: u64) -> u64 {
+        match self {
+            Spec::Add => x.wrapping_add(y),
+            Spec::Sub => x.wrapping_sub(y),
+            Spec::And => x & y,
+            Spec::Or => x | y,
+            Spec::Xor => x ^ y,
+            Spec::Less => (x < y) as u64,
+            Spec::Equal => (x == y) as u64,
+            Spec::Custom(f) => f(x, y),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counterexample {
+    pub x: u64,
+    pub y: u64,
+    pub got: u64,
+    pub expected: u64,
+}
+
+impl Counterexample {
+    pub fn lowest_failing_bit(&self) -> usize {
+        (self.got ^ self.expected).trailing_zeros() as usize
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecReport {
+    // x/y pairs simulated
+    pub vectors: usize,
+    // In the order they were simulated
+    pub counterexamples: Vec<Counterexample>,
+}
+
+impl SpecReport {
+    pub fn is_valid(&self) -> bool {
+        self.counterexamples.is_empty()
+    }
+
+    pub fn lowest_failing_bit(&self) -> Option<usize> {
+        self.counterexamples
+            .iter()
+            .map(|counterexample| counterexample.lowest_failing_bit())
+            .min()
+    }
+}
+
+impl Device {
+    // Simulates the device against `spec`, exhaustively for small operands and with the repair
+    // test vectors otherwise. None if x/y/z aren't numbered wires (x and y of the same width, all
+    // of at most 64 bits).
+    pub fn check(&self, spec: &Spec) -> Option<SpecReport> {
+        let width = self.numbered_wires('x')?.len();
+        let z_width = self.numbered_wires('z')?.len();
+
+        if self.numbered_wires('y')?.len() != width || width >= u64::BITS as usize {
+            return None;
+        }
+
+        let (x, y) = if width <= EXHAUSTIVE_BITS {
+            (0..1 << width)
+                .flat_map(|x| (0..1 << width).map(move |y| (x, y)))
+                .unzip()
+        } else {
+            test_vectors(width)
+        };
+
+        let z = self.compile()?.evaluate_batch(&x, &y);
+        let mask = u64::MAX
+            .checked_shr((u64::BITS as usize - z_width) as u32)
+            .unwrap_or(0);
+
+        Some(SpecReport {
+            vectors: z.len(),
+            counterexamples: (0..z.len())
+                .map(|i| Counterexample {
+                    x: x[i],
+                    y: y[i],
+                    got: z[i],
+                    expected: spec.evaluate(x[i], y[i]) & mask,
+                })
+                .filter(|counterexample| counterexample.got != counterexample.expected)
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::adder::tests::adder;
+
+    #[test]
+    fn test_check() {
+        let report = adder(4, &[]).check(&Spec::Add).unwrap();
+
+        assert_eq!(report.vectors, 256);
+        assert!(report.is_valid());
+        assert_eq!(report.lowest_failing_bit(), None);
+
+        let report = adder(8, &[("z05", "c05")]).check(&Spec::Add).unwrap();
+
+        assert!(!report.is_valid());
+        assert_eq!(report.lowest_failing_bit(), Some(5));
+        assert!(report
+            .counterexamples
+            .iter()
+            .all(|c| c.got != c.expected && c.expected == c.x + c.y));
+    }
+
+    #[test]
+    fn test_specs() {
+        // On 1 bit x XOR y is also x - y and x + y
+        let device = Device::parse("x00: 0\ny00: 0\n\nx00 XOR y00 -> z00").unwrap();
+
+        assert!(device.check(&Spec::Xor).unwrap().is_valid());
+        assert!(device.check(&Spec::Sub).unwrap().is_valid());
+        assert!(device
+            .check(&Spec::Custom(Box::new(|x, y| x + y)))
+            .unwrap()
+            .is_valid());
+
+        let report = device.check(&Spec::And).unwrap();
+        assert_eq!(
+            report.counterexamples,
+            vec![
+                Counterexample {
+                    x: 0,
+                    y: 1,
+                    got: 1,
+                    expected: 0
+                },
+                Counterexample {
+                    x: 1,
+                    y: 0,
+                    got: 1,
+                    expected: 0
+                },
+                Counterexample {
+                    x: 1,
+                    y: 1,
+                    got: 0,
+                    expected: 1
+                }
+            ]
+        );
+
+        // NOT x00 AND y00, x < y on 1 bit
+        let device = Device::parse("x00: 0\ny00: 0\n\nNOT x00 -> a\na AND y00 -> z00").unwrap();
+
+        assert!(device.check(&Spec::Less).unwrap().is_valid());
+        assert_eq!(
+            device.check(&Spec::Equal).unwrap().lowest_failing_bit(),
+            Some(0)
+        );
+
+        // Without z wires nothing is expected
+        let device = Device::parse("x00: 0\ny00: 0\n\nx00 XOR y00 -> out").unwrap();
+
+        assert!(device.check(&Spec::Xor).unwrap().is_valid());
+    }
+}
@@ -1,6 +1,7 @@
 use std::{fs, time::Instant};
 
-use device::Device;
+use device::{ConvergeError, Device, Spec};
+use lib::debugln;
 
 type ParsedInput = Device;
 type Output = u64;
@@ -10,11 +11,134 @@ mod device;
 fn main() {
     let input = input();
 
+    // Dump or check the netlist instead of solving, e.g. `cargo run -- --dot | dot -Tsvg > day-24.svg`
+    match std::env::args().nth(1).as_deref() {
+        Some("--dot") => return print!("{}", input.to_dot()),
+        Some("--mermaid") => return print!("{}", input.to_mermaid()),
+        Some("--verify") => return verify(&input),
+        Some("--analyse") => return analyse(&input),
+        Some("--check") => return check(&input, std::env::args().nth(2).as_deref()),
+        Some("--netlist") => return println!("{}", input.to_netlist()),
+        Some("--verilog") => return println!("{}", input.to_verilog("day24")),
+        Some("--blif") => return println!("{}", input.to_blif("day24")),
+        _ => {}
+    }
+
     let start = Instant::now();
-    println!("Result (part 1): {} [{:?}]", part1(&input), start.elapsed());
+    match part1(&input) {
+        Ok(result) => println!("Result (part 1): {} [{:?}]", result, start.elapsed()),
+        Err(error) => println!("Result (part 1): invalid device, {}", error),
+    }
 
     let start = Instant::now();
-    println!("Result (part 2): {} [{:?}]", part2(&input), start.elapsed());
+    match part2(&input) {
+        Ok(result) => println!("Result (part 2): {} [{:?}]", result, start.elapsed()),
+        Err(0) => println!("Result (part 2): no repair with at most 4 swaps"),
+        Err(n) => println!("Result (part 2): {} minimal repairs, no single answer", n),
+    }
+}
+
+// Adder issues of the netlist, bit by bit
+fn verify(input: &ParsedInput) {
+    let Some(report) = input.verify_adder() else {
+        return println!("Not an adder: x/y/z wires don't match");
+    };
+
+    println!(
+        "{}-bit adder{}",
+        report.width,
+        if report.carry_out {
+            " with carry out"
+        } else {
+            ""
+        }
+    );
+
+    if report.is_valid() {
+        println!("No issue found");
+    }
+
+    for bit in 0..report.width {
+        for issue in report.bit_issues(bit) {
+            println!("{}", issue);
+        }
+    }
+
+    // Carries generated at, and rippling up to, each bit
+    let (x, y): (Vec<u64>, Vec<u64>) = (0..report.width)
+        .flat_map(|bit| [(1 << bit, 1 << bit), ((1 << bit) - 1, 1)])
+        .unzip();
+
+    let z = input.compile().unwrap().evaluate_batch(&x, &y);
+    let failing = (0..z.len()).filter(|i| z[*i] != x[*i] + y[*i]).count();
+
+    println!("Simulation: {} of {} carry patterns fail", failing, z.len());
+}
+
+// Simulation against the intended function, addition by default
+fn check(input: &ParsedInput, spec: Option<&str>) {
+    let spec = match spec {
+        None | Some("add") => Spec::Add,
+        Some("sub") => Spec::Sub,
+        Some("and") => Spec::And,
+        Some("or") => Spec::Or,
+        Some("xor") => Spec::Xor,
+        Some("less") => Spec::Less,
+        Some("equal") => Spec::Equal,
+        Some("mul") => Spec::Custom(Box::new(|x, y| x.wrapping_mul(y))),
+        Some(spec) => return println!("Unknown spec {:?}", spec),
+    };
+
+    let Some(report) = input.check(&spec) else {
+        return println!("Not checkable: x/y/z wires don't match");
+    };
+
+    if report.is_valid() {
+        return println!("All {} vectors match", report.vectors);
+    }
+
+    println!(
+        "{} of {} vectors fail, lowest failing bit: z{:02}",
+        report.counterexamples.len(),
+        report.vectors,
+        report.lowest_failing_bit().unwrap()
+    );
+
+    for counterexample in report.counterexamples.iter().take(5) {
+        println!(
+            "x = {}, y = {}: got {}, expected {}",
+            counterexample.x, counterexample.y, counterexample.got, counterexample.expected
+        );
+    }
+}
+
+// Loops, logic depth and unused gates of the netlist
+fn analyse(input: &ParsedInput) {
+    for cycle in input.loops() {
+        println!("Loop: {}", cycle.join(" -> "));
+    }
+
+    match input.critical_path() {
+        Ok(path) => {
+            let depths = input.depths().unwrap();
+            let deepest = depths.iter().map(|(_, depth)| *depth).max().unwrap_or(0);
+
+            println!("Depth: {} gates over {} outputs", deepest, depths.len());
+            println!("Critical path: {}", path.join(" -> "));
+        }
+        Err(_) => println!("Depth: unbounded, the netlist has loops"),
+    }
+
+    if let Some((wire, fan_out)) = input
+        .wires()
+        .into_iter()
+        .map(|wire| (wire, input.fan_out(wire).unwrap().len()))
+        .max_by_key(|(wire, fan_out)| (*fan_out, std::cmp::Reverse(*wire)))
+    {
+        println!("Largest fan-out: {} ({} gates)", wire, fan_out);
+    }
+
+    println!("Dead gates: {:?}", input.dead_gates());
 }
 
 fn input() -> ParsedInput {
@@ -23,29 +147,30 @@ fn input() -> ParsedInput {
 }
 
 fn parse_input(input: &str) -> ParsedInput {
-    Device::parse(input)
+    Device::parse(input).unwrap_or_else(|error| panic!("Invalid netlist, {}", error))
 }
 
-fn part1(input: &ParsedInput) -> Output {
+fn part1(input: &ParsedInput) -> Result<Output, ConvergeError> {
     let mut input = input.clone();
-    let _ = input.converge();
+    input.converge()?;
 
     input.debug();
 
-    input.wires_number('z')
+    Ok(input
+        .wires_number('z')
+        .expect("Every z wire is resolved once converged"))
 }
 
-fn part2(input: &ParsedInput) -> String {
-    let input = input.clone();
-
-    let expected_value: u64 = input.wires_number('x') + input.wires_number('y');
+// Err with the number of minimal repairs unless there's exactly one
+fn part2(input: &ParsedInput) -> Result<String, usize> {
+    let repairs = input.repair(4, &Spec::Add);
 
-    let result = input.solve_switch(4, expected_value);
+    debugln!("{} minimal repairs: {:?}", repairs.len(), repairs);
 
-    let mut result: Vec<String> = result.into_iter().map(|v| v.to_string()).collect();
-    result.sort();
-
-    result.join(",")
+    match repairs.as_slice() {
+        [repair] => Ok(repair.wires().join(",")),
+        repairs => Err(repairs.len()),
+    }
 }
 
 #[cfg(test)]
@@ -125,13 +250,21 @@ x02 OR y02 -> z02
 "#
                 .trim()
             )),
-            4
+            Ok(4)
+        );
+        assert_eq!(
+            part1(&parse_input(
+                "x00: 1
+
+x00 AND q -> z00"
+            )),
+            Err(ConvergeError::Undefined("q".to_string()))
         );
     }
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&input()), 2024);
+        assert_eq!(part1(&input()), Ok(2024));
     }
 
     #[test]
@@ -161,13 +294,10 @@ x05 AND y05 -> z00
             .trim(),
         );
 
-        let mut result: Vec<String> = input
-            .solve_switch(2, input.wires_number('x') & input.wires_number('y'))
-            .into_iter()
-            .map(|v| v.to_string())
-            .collect();
-        result.sort();
+        let repairs = input.repair(2, &Spec::And);
 
-        assert_eq!(result.join(","), "z00,z01,z02,z05");
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(repairs[0].wires().join(","), "z00,z01,z02,z05");
+        assert_eq!(input.repair(1, &Spec::And).len(), 0);
     }
 }
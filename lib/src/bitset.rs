@@ -0,0 +1,186 @@
+#[derive(Clone, Default)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        BitSet { words: Vec::new() }
+    }
+
+    pub fn with_capacity(n_bits: usize) -> Self {
+        BitSet {
+            words: vec![0; n_bits.div_ceil(64)],
+        }
+    }
+
+    pub fn insert(&mut self, index: usize) -> bool {
+        let (word, mask) = (index / 64, 1 << (index % 64));
+
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+
+        let inserted = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+
+        inserted
+    }
+
+    pub fn remove(&mut self, index: usize) -> bool {
+        let (word, mask) = (index / 64, 1 << (index % 64));
+
+        match self.words.get_mut(word) {
+            Some(w) if *w & mask != 0 => {
+                *w &= !mask;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.words
+            .get(index / 64)
+            .is_some_and(|w| w & (1 << (index % 64)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = 0);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, w)| {
+            let mut w = *w;
+
+            std::iter::from_fn(move || {
+                if w == 0 {
+                    return None;
+                }
+
+                let bit = w.trailing_zeros() as usize;
+                w &= w - 1;
+
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    // Lowest index in the set
+    pub fn first(&self) -> Option<usize> {
+        self.iter().next()
+    }
+
+    pub fn union_with(&mut self, other: &BitSet) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+
+        for (w, o) in self.words.iter_mut().zip(other.words.iter()) {
+            *w |= o;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        for (i, w) in self.words.iter_mut().enumerate() {
+            *w &= other.words.get(i).copied().unwrap_or(0);
+        }
+    }
+
+    pub fn difference_with(&mut self, other: &BitSet) {
+        for (w, o) in self.words.iter_mut().zip(other.words.iter()) {
+            *w &= !o;
+        }
+    }
+
+    pub fn intersection(&self, other: &BitSet) -> BitSet {
+        let mut result = self.clone();
+        result.intersect_with(other);
+        result
+    }
+
+    pub fn difference(&self, other: &BitSet) -> BitSet {
+        let mut result = self.clone();
+        result.difference_with(other);
+        result
+    }
+
+    pub fn intersection_len(&self, other: &BitSet) -> usize {
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = BitSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for i in iter {
+            self.insert(i);
+        }
+    }
+}
+
+// Trailing zero words don't matter as the set grows on insert
+impl PartialEq for BitSet {
+    fn eq(&self, other: &Self) -> bool {
+        let len = self.words.len().max(other.words.len());
+
+        (0..len).all(|i| self.words.get(i).unwrap_or(&0) == other.words.get(i).unwrap_or(&0))
+    }
+}
+
+impl Eq for BitSet {}
+
+impl std::fmt::Debug for BitSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitset() {
+        let mut set: BitSet = [1, 64, 130].into_iter().collect();
+
+        assert!(set.contains(64));
+        assert!(!set.contains(65));
+        assert!(!set.contains(10_000));
+        assert!(!set.insert(1));
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 64, 130]);
+
+        let other: BitSet = [64, 130, 200].into_iter().collect();
+
+        assert_eq!(
+            set.intersection(&other).iter().collect::<Vec<_>>(),
+            vec![64, 130]
+        );
+        assert_eq!(set.difference(&other).iter().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(set.intersection_len(&other), 2);
+
+        assert!(set.remove(1));
+        assert!(!set.remove(1));
+        assert_eq!(set.first(), Some(64));
+    }
+}
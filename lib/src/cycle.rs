@@ -0,0 +1,182 @@
+use std::{collections::HashMap, hash::Hash};
+
+// A sequence x0, f(x0), f(f(x0)), ... enters a cycle at index `start`, `entry` being the state
+// at that index, and repeats every `length` steps from there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<S> {
+    pub start: usize,
+    pub length: usize,
+    pub entry: S,
+}
+
+// Floyd (tortoise and hare), constant memory
+pub fn floyd<S, F>(initial: S, f: F) -> Cycle<S>
+where
+    S: Clone + PartialEq,
+    F: Fn(&S) -> S,
+{
+    let mut tortoise = f(&initial);
+    let mut hare = f(&tortoise);
+
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&f(&hare));
+    }
+
+    let mut start = 0;
+    tortoise = initial;
+
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = f(&tortoise);
+
+    while tortoise != hare {
+        hare = f(&hare);
+        length += 1;
+    }
+
+    Cycle {
+        start,
+        length,
+        entry: tortoise,
+    }
+}
+
+// Brent, constant memory and fewer calls to f than Floyd
+pub fn brent<S, F>(initial: S, f: F) -> Cycle<S>
+where
+    S: Clone + PartialEq,
+    F: Fn(&S) -> S,
+{
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = f(&initial);
+
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+
+        hare = f(&hare);
+        length += 1;
+    }
+
+    tortoise = initial.clone();
+    hare = initial;
+
+    for _ in 0..length {
+        hare = f(&hare);
+    }
+
+    let mut start = 0;
+
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        start += 1;
+    }
+
+    Cycle {
+        start,
+        length,
+        entry: tortoise,
+    }
+}
+
+// Keeps every visited state, f is called exactly start + length times
+pub fn find_cycle<S, F>(initial: S, f: F) -> Cycle<S>
+where
+    S: Clone + Eq + Hash,
+    F: Fn(&S) -> S,
+{
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut state = initial;
+
+    for step in 0.. {
+        if let Some(&start) = seen.get(&state) {
+            return Cycle {
+                start,
+                length: step - start,
+                entry: state,
+            };
+        }
+
+        let next = f(&state);
+        seen.insert(state, step);
+        state = next;
+    }
+
+    unreachable!()
+}
+
+// State after n applications of f, skipping all the whole cycles once one is detected
+pub fn fast_forward<S, F>(initial: S, f: F, n: u64) -> S
+where
+    S: Clone + Eq + Hash,
+    F: Fn(&S) -> S,
+{
+    let mut seen: HashMap<S, u64> = HashMap::new();
+    let mut history: Vec<S> = vec![];
+    let mut state = initial;
+
+    for step in 0..n {
+        if let Some(&start) = seen.get(&state) {
+            let length = step - start;
+
+            return history[(start + (n - start) % length) as usize].clone();
+        }
+
+        let next = f(&state);
+
+        seen.insert(state.clone(), step);
+        history.push(state);
+
+        state = next;
+    }
+
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2 -> 4 -> 16 -> 56 -> 36 -> 96 -> 16 -> ...
+    fn square_mod(n: &u64) -> u64 {
+        n * n % 100
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        let expected = Cycle {
+            start: 2,
+            length: 4,
+            entry: 16,
+        };
+
+        assert_eq!(floyd(2, square_mod), expected);
+        assert_eq!(brent(2, square_mod), expected);
+        assert_eq!(find_cycle(2, square_mod), expected);
+    }
+
+    #[test]
+    fn test_fast_forward() {
+        let mut state = 2;
+
+        for n in 0..20 {
+            assert_eq!(fast_forward(2, square_mod, n), state);
+            state = square_mod(&state);
+        }
+
+        // (10^15 - 2) % 4 = 2 so we land 2 steps after the cycle entry
+        assert_eq!(fast_forward(2, square_mod, 1_000_000_000_000_000), 36);
+    }
+}
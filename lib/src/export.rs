@@ -0,0 +1,264 @@
+use std::{collections::HashSet, fmt::Write, hash::Hash};
+
+use crate::graph::{Graph, NodeId};
+
+type Labeler<'a, T> = Box<dyn Fn(&T) -> String + 'a>;
+type Clusterer<'a> = Box<dyn Fn(&str) -> Option<String> + 'a>;
+
+// How to render a graph, e.g.
+// `ExportOptions::new(|n: &String| n.clone()).highlight(clique, "red").cluster_by_prefix(&["x"])`
+pub struct ExportOptions<'a, N, E> {
+    node_label: Labeler<'a, N>,
+    edge_label: Option<Labeler<'a, E>>,
+    // First matching set wins
+    highlights: Vec<(HashSet<NodeId>, String)>,
+    cluster: Option<Clusterer<'a>>,
+}
+
+impl<'a, N, E> ExportOptions<'a, N, E> {
+    pub fn new(node_label: impl Fn(&N) -> String + 'a) -> Self {
+        ExportOptions {
+            node_label: Box::new(node_label),
+            edge_label: None,
+            highlights: vec![],
+            cluster: None,
+        }
+    }
+
+    pub fn edge_label(mut self, edge_label: impl Fn(&E) -> String + 'a) -> Self {
+        self.edge_label = Some(Box::new(edge_label));
+        self
+    }
+
+    pub fn highlight(mut self, nodes: impl IntoIterator<Item = NodeId>, color: &str) -> Self {
+        self.highlights
+            .push((nodes.into_iter().collect(), color.to_string()));
+        self
+    }
+
+    // Groups nodes by the cluster name returned for their label
+    pub fn cluster_by(mut self, cluster: impl Fn(&str) -> Option<String> + 'a) -> Self {
+        self.cluster = Some(Box::new(cluster));
+        self
+    }
+
+    pub fn cluster_by_prefix(self, prefixes: &'a [&'a str]) -> Self {
+        self.cluster_by(|label| {
+            prefixes
+                .iter()
+                .find(|prefix| label.starts_with(**prefix))
+                .map(|prefix| prefix.to_string())
+        })
+    }
+
+    fn color(&self, id: NodeId) -> Option<&str> {
+        self.highlights
+            .iter()
+            .find(|(nodes, _)| nodes.contains(&id))
+            .map(|(_, color)| color.as_str())
+    }
+}
+
+impl<N, E> Graph<N, E>
+where
+    N: Hash + Eq,
+{
+    pub fn to_dot(&self, options: &ExportOptions<N, E>) -> String {
+        let mut out = String::new();
+        let (kind, arrow) = if self.is_directed() {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+
+        writeln!(out, "{} {{", kind).unwrap();
+
+        for (cluster, nodes) in self.clusters(options) {
+            let indent = match &cluster {
+                Some(name) => {
+                    writeln!(out, "    subgraph \"cluster_{}\" {{", escape(name)).unwrap();
+                    writeln!(out, "        label=\"{}\";", escape(name)).unwrap();
+                    "        "
+                }
+                None => "    ",
+            };
+
+            for id in nodes {
+                let label = escape(&(options.node_label)(self.node(id)));
+
+                write!(out, "{}n{} [label=\"{}\"", indent, id.index(), label).unwrap();
+
+                if let Some(color) = options.color(id) {
+                    write!(out, ", style=filled, fillcolor=\"{}\"", escape(color)).unwrap();
+                }
+
+                writeln!(out, "];").unwrap();
+            }
+
+            if cluster.is_some() {
+                writeln!(out, "    }}").unwrap();
+            }
+        }
+
+        for (from, to, weight) in self.unique_edges() {
+            write!(out, "    n{} {} n{}", from.index(), arrow, to.index()).unwrap();
+
+            if let Some(edge_label) = &options.edge_label {
+                write!(out, " [label=\"{}\"]", escape(&edge_label(weight))).unwrap();
+            }
+
+            writeln!(out, ";").unwrap();
+        }
+
+        writeln!(out, "}}").unwrap();
+
+        out
+    }
+
+    pub fn to_mermaid(&self, options: &ExportOptions<N, E>) -> String {
+        let mut out = String::new();
+        let arrow = if self.is_directed() { "-->" } else { "---" };
+
+        writeln!(out, "flowchart LR").unwrap();
+
+        for (cluster, nodes) in self.clusters(options) {
+            let indent = match &cluster {
+                Some(name) => {
+                    writeln!(out, "    subgraph {}", mermaid_escape(name)).unwrap();
+                    "        "
+                }
+                None => "    ",
+            };
+
+            for id in nodes {
+                let label = mermaid_escape(&(options.node_label)(self.node(id)));
+
+                writeln!(out, "{}n{}[\"{}\"]", indent, id.index(), label).unwrap();
+            }
+
+            if cluster.is_some() {
+                writeln!(out, "    end").unwrap();
+            }
+        }
+
+        for (from, to, weight) in self.unique_edges() {
+            write!(out, "    n{} {}", from.index(), arrow).unwrap();
+
+            if let Some(edge_label) = &options.edge_label {
+                write!(out, "|\"{}\"|", mermaid_escape(&edge_label(weight))).unwrap();
+            }
+
+            writeln!(out, " n{}", to.index()).unwrap();
+        }
+
+        for id in self.node_ids() {
+            if let Some(color) = options.color(id) {
+                writeln!(out, "    style n{} fill:{}", id.index(), color).unwrap();
+            }
+        }
+
+        out
+    }
+
+    // Unclustered nodes first, then clusters by name
+    fn clusters(&self, options: &ExportOptions<N, E>) -> Vec<(Option<String>, Vec<NodeId>)> {
+        let mut clusters: Vec<(Option<String>, Vec<NodeId>)> = vec![];
+
+        for (id, node) in self.nodes() {
+            let cluster = options
+                .cluster
+                .as_ref()
+                .and_then(|cluster| cluster(&(options.node_label)(node)));
+
+            match clusters.iter_mut().find(|(name, _)| *name == cluster) {
+                Some((_, nodes)) => nodes.push(id),
+                None => clusters.push((cluster, vec![id])),
+            }
+        }
+
+        clusters.sort_by(|a, b| a.0.cmp(&b.0));
+
+        clusters
+    }
+
+    // Undirected edges are stored both ways, only keep one of them
+    fn unique_edges(&self) -> impl Iterator<Item = (NodeId, NodeId, &E)> {
+        self.node_ids().flat_map(move |from| {
+            self.edges(from)
+                .iter()
+                .filter(move |(to, _)| self.is_directed() || *to >= from)
+                .map(move |(to, weight)| (from, *to, weight))
+        })
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph<String, &'static str> {
+        let mut graph = Graph::directed();
+
+        graph.connect("x00".to_string(), "z00".to_string(), "XOR");
+        graph.connect("y00".to_string(), "z00".to_string(), "XOR");
+
+        graph
+    }
+
+    #[test]
+    fn test_dot() {
+        let graph = graph();
+        let options = ExportOptions::new(|n: &String| n.clone())
+            .edge_label(|e: &&str| e.to_string())
+            .highlight(graph.node_id(&"z00".to_string()), "red")
+            .cluster_by_prefix(&["x", "y"]);
+
+        assert_eq!(
+            graph.to_dot(&options),
+            r#"digraph {
+    n1 [label="z00", style=filled, fillcolor="red"];
+    subgraph "cluster_x" {
+        label="x";
+        n0 [label="x00"];
+    }
+    subgraph "cluster_y" {
+        label="y";
+        n2 [label="y00"];
+    }
+    n0 -> n1 [label="XOR"];
+    n2 -> n1 [label="XOR"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_mermaid() {
+        let mut graph: Graph<u32> = Graph::undirected();
+        graph.connect(1, 2, ());
+        graph.connect(2, 3, ());
+
+        let options =
+            ExportOptions::new(|n: &u32| n.to_string()).highlight(graph.node_id(&2), "red");
+
+        assert_eq!(
+            graph.to_mermaid(&options),
+            r#"flowchart LR
+    n0["1"]
+    n1["2"]
+    n2["3"]
+    n0 --- n1
+    n1 --- n2
+    style n1 fill:red
+"#
+        );
+    }
+}
@@ -0,0 +1,742 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+};
+
+use crate::bitset::BitSet;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+// Adjacency list graph, nodes are interned so they can be added and looked up by value while
+// algorithms work on dense ids
+#[derive(Clone, Debug)]
+pub struct Graph<N, E = ()> {
+    directed: bool,
+    nodes: Vec<N>,
+    index: HashMap<N, NodeId>,
+    out_edges: Vec<Vec<(NodeId, E)>>,
+    // Only used for directed graphs, undirected edges are stored both ways in `out_edges`
+    in_edges: Vec<Vec<NodeId>>,
+    n_edges: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DegreeStats {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+}
+
+impl<N, E> Graph<N, E>
+where
+    N: Hash + Eq + Clone,
+    E: Clone,
+{
+    pub fn directed() -> Self {
+        Self::new(true)
+    }
+
+    pub fn undirected() -> Self {
+        Self::new(false)
+    }
+
+    fn new(directed: bool) -> Self {
+        Graph {
+            directed,
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            out_edges: Vec::new(),
+            in_edges: Vec::new(),
+            n_edges: 0,
+        }
+    }
+
+    pub fn add_node(&mut self, node: N) -> NodeId {
+        if let Some(id) = self.index.get(&node) {
+            return *id;
+        }
+
+        let id = NodeId(self.nodes.len());
+
+        self.index.insert(node.clone(), id);
+        self.nodes.push(node);
+        self.out_edges.push(Vec::new());
+        self.in_edges.push(Vec::new());
+
+        id
+    }
+
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: E) {
+        if self.directed {
+            self.in_edges[to.0].push(from);
+        } else if from != to {
+            self.out_edges[to.0].push((from, weight.clone()));
+        }
+
+        self.out_edges[from.0].push((to, weight));
+        self.n_edges += 1;
+    }
+
+    // Adds both nodes if needed
+    pub fn connect(&mut self, from: N, to: N, weight: E) -> (NodeId, NodeId) {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+
+        self.add_edge(from, to, weight);
+
+        (from, to)
+    }
+
+    // Graph restricted to the kept nodes and the edges between them
+    pub fn subgraph(&self, keep: impl Fn(NodeId, &N) -> bool) -> Self {
+        let mut graph = Self::new(self.directed);
+
+        let kept: Vec<Option<NodeId>> = self
+            .nodes()
+            .map(|(id, node)| keep(id, node).then(|| graph.add_node(node.clone())))
+            .collect();
+
+        for (from, edges) in self.out_edges.iter().enumerate() {
+            let Some(new_from) = kept[from] else {
+                continue;
+            };
+
+            for (to, weight) in edges {
+                // Undirected edges are stored twice, only copy them once
+                if !self.directed && to.0 < from {
+                    continue;
+                }
+
+                if let Some(new_to) = kept[to.0] {
+                    graph.add_edge(new_from, new_to, weight.clone());
+                }
+            }
+        }
+
+        graph
+    }
+}
+
+impl<N, E> Graph<N, E>
+where
+    N: Hash + Eq,
+{
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.n_edges
+    }
+
+    pub fn node_id(&self, node: &N) -> Option<NodeId> {
+        self.index.get(node).copied()
+    }
+
+    pub fn node(&self, id: NodeId) -> &N {
+        &self.nodes[id.0]
+    }
+
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.nodes.len()).map(NodeId)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &N)> {
+        self.nodes.iter().enumerate().map(|(i, n)| (NodeId(i), n))
+    }
+
+    // Outgoing edges (all edges for an undirected graph)
+    pub fn edges(&self, id: NodeId) -> &[(NodeId, E)] {
+        &self.out_edges[id.0]
+    }
+
+    pub fn neighboors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.out_edges[id.0].iter().map(|(to, _)| *to)
+    }
+
+    // Incoming edges (all edges for an undirected graph)
+    pub fn predecessors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let (incoming, undirected): (&[NodeId], &[(NodeId, E)]) = if self.directed {
+            (&self.in_edges[id.0], &[])
+        } else {
+            (&[], &self.out_edges[id.0])
+        };
+
+        incoming
+            .iter()
+            .copied()
+            .chain(undirected.iter().map(|(from, _)| *from))
+    }
+
+    pub fn has_edge(&self, from: NodeId, to: NodeId) -> bool {
+        self.neighboors(from).any(|n| n == to)
+    }
+
+    pub fn out_degree(&self, id: NodeId) -> usize {
+        self.out_edges[id.0].len()
+    }
+
+    pub fn in_degree(&self, id: NodeId) -> usize {
+        if self.directed {
+            self.in_edges[id.0].len()
+        } else {
+            self.out_degree(id)
+        }
+    }
+
+    pub fn degree(&self, id: NodeId) -> usize {
+        if self.directed {
+            self.in_degree(id) + self.out_degree(id)
+        } else {
+            self.out_degree(id)
+        }
+    }
+
+    pub fn degree_stats(&self) -> Option<DegreeStats> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let degrees: Vec<usize> = self.node_ids().map(|id| self.degree(id)).collect();
+
+        Some(DegreeStats {
+            min: *degrees.iter().min().unwrap(),
+            max: *degrees.iter().max().unwrap(),
+            mean: degrees.iter().sum::<usize>() as f64 / degrees.len() as f64,
+        })
+    }
+
+    // Kahn's algorithm. On failure returns one of the cycles, as a path where the last node
+    // links back to the first.
+    pub fn toposort(&self) -> Result<Vec<NodeId>, Vec<NodeId>> {
+        assert!(self.directed, "Topological sort needs a directed graph");
+
+        let mut in_degrees: Vec<usize> = self.in_edges.iter().map(|e| e.len()).collect();
+        let mut queue: VecDeque<NodeId> =
+            self.node_ids().filter(|id| in_degrees[id.0] == 0).collect();
+
+        let mut order = Vec::with_capacity(self.len());
+
+        while let Some(id) = queue.pop_front() {
+            order.push(id);
+
+            for next in self.neighboors(id) {
+                in_degrees[next.0] -= 1;
+
+                if in_degrees[next.0] == 0 {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        if order.len() == self.len() {
+            return Ok(order);
+        }
+
+        // Every node left has a predecessor which is also left, walking them backward must
+        // end up in a loop
+        let mut position = vec![None; self.len()];
+        let mut path = vec![];
+        let mut current = self.node_ids().find(|id| in_degrees[id.0] > 0).unwrap();
+
+        while position[current.0].is_none() {
+            position[current.0] = Some(path.len());
+            path.push(current);
+
+            current = self.in_edges[current.0]
+                .iter()
+                .copied()
+                .find(|p| in_degrees[p.0] > 0)
+                .unwrap();
+        }
+
+        let mut cycle = path.split_off(position[current.0].unwrap());
+        cycle.reverse();
+
+        Err(cycle)
+    }
+
+    // Tarjan's algorithm, components are returned in reverse topological order
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut state = TarjanState {
+            index: vec![None; self.len()],
+            low_link: vec![0; self.len()],
+            on_stack: vec![false; self.len()],
+            stack: vec![],
+            next_index: 0,
+            components: vec![],
+        };
+
+        for id in self.node_ids() {
+            if state.index[id.0].is_none() {
+                self.tarjan(id, &mut state);
+            }
+        }
+
+        state.components
+    }
+
+    fn tarjan(&self, id: NodeId, state: &mut TarjanState) {
+        state.index[id.0] = Some(state.next_index);
+        state.low_link[id.0] = state.next_index;
+        state.next_index += 1;
+        state.stack.push(id);
+        state.on_stack[id.0] = true;
+
+        for next in self.neighboors(id) {
+            match state.index[next.0] {
+                None => {
+                    self.tarjan(next, state);
+                    state.low_link[id.0] = state.low_link[id.0].min(state.low_link[next.0]);
+                }
+                Some(next_index) if state.on_stack[next.0] => {
+                    state.low_link[id.0] = state.low_link[id.0].min(next_index);
+                }
+                _ => {}
+            }
+        }
+
+        if Some(state.low_link[id.0]) == state.index[id.0] {
+            let mut component = vec![];
+
+            loop {
+                let node = state.stack.pop().unwrap();
+                state.on_stack[node.0] = false;
+                component.push(node);
+
+                if node == id {
+                    break;
+                }
+            }
+
+            component.sort();
+            state.components.push(component);
+        }
+    }
+
+    // Weakly connected components for directed graphs
+    pub fn connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut component_of = vec![None; self.len()];
+        let mut components = vec![];
+
+        for start in self.node_ids() {
+            if component_of[start.0].is_some() {
+                continue;
+            }
+
+            let mut component = vec![];
+            let mut queue = VecDeque::from([start]);
+            component_of[start.0] = Some(components.len());
+
+            while let Some(id) = queue.pop_front() {
+                component.push(id);
+
+                for next in self.neighboors(id).chain(self.predecessors(id)) {
+                    if component_of[next.0].is_none() {
+                        component_of[next.0] = Some(components.len());
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            component.sort();
+            components.push(component);
+        }
+
+        components
+    }
+
+    // Each triangle once, as sorted ids. Edge direction is ignored.
+    pub fn triangles(&self) -> Vec<[NodeId; 3]> {
+        let adjacency = self.undirected_adjacency();
+        let mut triangles = vec![];
+
+        for a in self.node_ids() {
+            for b in adjacency[a.0].iter().filter(|b| *b > a.0) {
+                for c in adjacency[a.0]
+                    .intersection(&adjacency[b])
+                    .iter()
+                    .filter(|c| *c > b)
+                {
+                    triangles.push([a, NodeId(b), NodeId(c)]);
+                }
+            }
+        }
+
+        triangles
+    }
+
+    // Bron–Kerbosch with pivoting, edge direction is ignored. Each clique is sorted.
+    pub fn maximal_cliques(&self) -> Vec<Vec<NodeId>> {
+        let CliqueSearch::All(cliques) = self.clique_search(CliqueSearch::All(vec![])) else {
+            unreachable!()
+        };
+
+        cliques
+    }
+
+    // Sorted, branches which can't beat the largest clique found so far are skipped
+    pub fn max_clique(&self) -> Vec<NodeId> {
+        let CliqueSearch::Largest(clique) = self.clique_search(CliqueSearch::Largest(vec![]))
+        else {
+            unreachable!()
+        };
+
+        clique
+    }
+
+    // Each node only looks for cliques among its neighboors later in the ordering, which are at
+    // most the degeneracy of the graph
+    fn clique_search(&self, mut search: CliqueSearch) -> CliqueSearch {
+        let adjacency = self.undirected_adjacency();
+        let mut p: BitSet = (0..self.len()).collect();
+        let mut x = BitSet::new();
+
+        for v in self.degeneracy_ordering() {
+            let n_v = &adjacency[v.0];
+
+            bron_kerbosch(
+                &adjacency,
+                &mut vec![v],
+                p.intersection(n_v),
+                x.intersection(n_v),
+                &mut search,
+            );
+
+            p.remove(v.0);
+            x.insert(v.0);
+        }
+
+        search
+    }
+
+    // Nodes removed one by one, the one with the fewest remaining neighboors first. Edge
+    // direction is ignored.
+    pub fn degeneracy_ordering(&self) -> Vec<NodeId> {
+        let adjacency = self.undirected_adjacency();
+        let mut degrees: Vec<usize> = adjacency.iter().map(|n_v| n_v.len()).collect();
+        let mut removed = vec![false; self.len()];
+        let mut order = Vec::with_capacity(self.len());
+
+        for _ in 0..self.len() {
+            let v = (0..self.len())
+                .filter(|v| !removed[*v])
+                .min_by_key(|v| degrees[*v])
+                .unwrap();
+
+            removed[v] = true;
+            order.push(NodeId(v));
+
+            for u in adjacency[v].iter() {
+                if !removed[u] {
+                    degrees[u] -= 1;
+                }
+            }
+        }
+
+        order
+    }
+
+    // Every clique of `size` nodes, maximal or not, generated lazily. Edge direction is ignored.
+    pub fn cliques_of_size(&self, size: usize) -> Cliques<'_, N, E> {
+        let all: BitSet = (0..self.len()).collect();
+
+        Cliques {
+            graph: self,
+            adjacency: self.undirected_adjacency(),
+            size,
+            required: all.clone(),
+            stack: vec![(vec![], all)],
+        }
+    }
+
+    // Without self loops
+    fn undirected_adjacency(&self) -> Vec<BitSet> {
+        let mut adjacency = vec![BitSet::with_capacity(self.len()); self.len()];
+
+        for id in self.node_ids() {
+            for next in self.neighboors(id).filter(|next| *next != id) {
+                adjacency[id.0].insert(next.0);
+                adjacency[next.0].insert(id.0);
+            }
+        }
+
+        adjacency
+    }
+}
+
+struct TarjanState {
+    index: Vec<Option<usize>>,
+    low_link: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<NodeId>,
+    next_index: usize,
+    components: Vec<Vec<NodeId>>,
+}
+
+// What Bron–Kerbosch keeps of the maximal cliques it finds
+enum CliqueSearch {
+    All(Vec<Vec<NodeId>>),
+    Largest(Vec<NodeId>),
+}
+
+fn bron_kerbosch(
+    adjacency: &[BitSet],
+    r: &mut Vec<NodeId>,
+    mut p: BitSet,
+    mut x: BitSet,
+    search: &mut CliqueSearch,
+) {
+    if let CliqueSearch::Largest(best) = search {
+        if r.len() + p.len() <= best.len() {
+            return;
+        }
+    }
+
+    if p.is_empty() {
+        if x.is_empty() {
+            let mut clique = r.clone();
+            clique.sort();
+
+            match search {
+                CliqueSearch::All(cliques) => cliques.push(clique),
+                CliqueSearch::Largest(best) => *best = clique,
+            }
+        }
+
+        return;
+    }
+
+    // Any maximal clique contains the pivot or one of its non neighboors, so only those are
+    // branched on. The pivot with the most neighboors in P leaves the fewest branches.
+    let pivot = p
+        .iter()
+        .chain(x.iter())
+        .max_by_key(|u| p.intersection_len(&adjacency[*u]))
+        .unwrap();
+
+    for v in p.difference(&adjacency[pivot]).iter() {
+        r.push(NodeId(v));
+
+        bron_kerbosch(
+            adjacency,
+            r,
+            p.intersection(&adjacency[v]),
+            x.intersection(&adjacency[v]),
+            search,
+        );
+
+        r.pop();
+        p.remove(v);
+        x.insert(v);
+    }
+}
+
+// Cliques in increasing order of ids, each one sorted too
+pub struct Cliques<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    adjacency: Vec<BitSet>,
+    size: usize,
+    // Nodes each clique must contain at least one of
+    required: BitSet,
+    // Partial cliques and the nodes which can extend them: linked to all of their nodes, and
+    // after them so every clique is only built once
+    stack: Vec<(Vec<usize>, BitSet)>,
+}
+
+impl<N, E> Cliques<'_, N, E>
+where
+    N: Hash + Eq,
+{
+    // Only the cliques with a node matching `predicate`
+    pub fn containing(mut self, predicate: impl Fn(&N) -> bool) -> Self {
+        self.required = self
+            .graph
+            .nodes()
+            .filter(|(_, node)| predicate(node))
+            .map(|(id, _)| id.0)
+            .collect();
+
+        self
+    }
+}
+
+impl<N, E> Iterator for Cliques<'_, N, E> {
+    type Item = Vec<NodeId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((clique, candidates)) = self.stack.pop() {
+            let matched = clique.iter().any(|v| self.required.contains(*v));
+
+            if clique.len() == self.size {
+                if matched {
+                    return Some(clique.into_iter().map(NodeId).collect());
+                }
+
+                continue;
+            }
+
+            if candidates.len() < self.size - clique.len()
+                || (!matched && candidates.intersection_len(&self.required) == 0)
+            {
+                continue;
+            }
+
+            let mut rest = candidates;
+            let mut children = vec![];
+
+            for v in rest.clone().iter() {
+                rest.remove(v);
+
+                let mut next = clique.clone();
+                next.push(v);
+
+                children.push((next, rest.intersection(&self.adjacency[v])));
+            }
+
+            // Popped in increasing order
+            self.stack.extend(children.into_iter().rev());
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids<N: Hash + Eq>(graph: &Graph<N>, nodes: &[N]) -> Vec<NodeId> {
+        nodes.iter().map(|n| graph.node_id(n).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_toposort() {
+        let mut graph = Graph::directed();
+
+        for (a, b) in [(97, 75), (75, 47), (47, 61), (97, 61), (61, 53)] {
+            graph.connect(a, b, ());
+        }
+
+        assert_eq!(graph.toposort(), Ok(ids(&graph, &[97, 75, 47, 61, 53])));
+
+        graph.connect(53, 75, ());
+
+        assert_eq!(graph.toposort(), Err(ids(&graph, &[47, 61, 53, 75])));
+    }
+
+    #[test]
+    fn test_components() {
+        let mut graph = Graph::directed();
+
+        for (a, b) in [(1, 2), (2, 3), (3, 1), (3, 4), (5, 6)] {
+            graph.connect(a, b, ());
+        }
+
+        assert_eq!(
+            graph.strongly_connected_components(),
+            vec![
+                ids(&graph, &[4]),
+                ids(&graph, &[1, 2, 3]),
+                ids(&graph, &[6]),
+                ids(&graph, &[5])
+            ]
+        );
+        assert_eq!(
+            graph.connected_components(),
+            vec![ids(&graph, &[1, 2, 3, 4]), ids(&graph, &[5, 6])]
+        );
+        assert_eq!(
+            graph.degree_stats(),
+            Some(DegreeStats {
+                min: 1,
+                max: 3,
+                mean: 10.0 / 6.0
+            })
+        );
+    }
+
+    #[test]
+    fn test_cliques() {
+        let mut graph = Graph::undirected();
+
+        for (a, b) in [(1, 5), (1, 2), (2, 5), (2, 3), (5, 4), (4, 3), (6, 4)] {
+            graph.connect(a, b, ());
+        }
+
+        let mut cliques: Vec<Vec<u32>> = graph
+            .maximal_cliques()
+            .into_iter()
+            .map(|c| {
+                let mut c: Vec<u32> = c.into_iter().map(|id| *graph.node(id)).collect();
+                c.sort();
+                c
+            })
+            .collect();
+        cliques.sort();
+
+        assert_eq!(
+            cliques,
+            vec![
+                vec![1, 2, 5],
+                vec![2, 3],
+                vec![3, 4],
+                vec![4, 5],
+                vec![4, 6]
+            ]
+        );
+        assert_eq!(
+            graph.triangles(),
+            vec![<[NodeId; 3]>::try_from(ids(&graph, &[1, 5, 2])).unwrap()]
+        );
+        assert_eq!(graph.max_clique(), ids(&graph, &[1, 5, 2]));
+        assert_eq!(
+            graph.degeneracy_ordering(),
+            ids(&graph, &[6, 1, 5, 2, 3, 4])
+        );
+    }
+
+    #[test]
+    fn test_cliques_of_size() {
+        let mut graph = Graph::undirected();
+
+        for (a, b) in [(1, 5), (1, 2), (2, 5), (2, 3), (5, 4), (4, 3), (6, 4)] {
+            graph.connect(a, b, ());
+        }
+
+        assert_eq!(graph.cliques_of_size(2).count(), 7);
+        assert_eq!(
+            graph.cliques_of_size(3).collect::<Vec<_>>(),
+            vec![ids(&graph, &[1, 5, 2])]
+        );
+        assert_eq!(graph.cliques_of_size(4).count(), 0);
+        assert_eq!(
+            graph
+                .cliques_of_size(2)
+                .containing(|node| *node == 4)
+                .collect::<Vec<_>>(),
+            vec![
+                ids(&graph, &[5, 4]),
+                ids(&graph, &[3, 4]),
+                ids(&graph, &[4, 6])
+            ]
+        );
+
+        assert!(Graph::<u32>::undirected().max_clique().is_empty());
+    }
+}
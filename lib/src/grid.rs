@@ -27,6 +27,13 @@ impl<T> Grid2d<T> {
         Grid2d { values }
     }
 
+    pub fn size(&self) -> (usize, usize) {
+        (
+            self.values.len(),
+            self.values.first().map(|l| l.len()).unwrap_or(0),
+        )
+    }
+
     pub fn at(&self, pos: &Point) -> Option<&T> {
         if pos.x < 0
             || pos.y < 0
@@ -51,15 +58,15 @@ impl<T> Grid2d<T> {
         Some(&mut self.values[pos.x as usize][pos.y as usize])
     }
 
-    pub fn iter(&self) -> iterator::Grid2dLines<T> {
+    pub fn iter(&self) -> iterator::Grid2dLines<'_, T> {
         iterator::Grid2dLines::new(&self.values)
     }
 
-    pub fn cardinal_neighboors(&self, pos: Point) -> iterator::CardinalNeighboors<T> {
+    pub fn cardinal_neighboors(&self, pos: Point) -> iterator::CardinalNeighboors<'_, T> {
         iterator::CardinalNeighboors::new(self, pos)
     }
 
-    pub fn all_neighboors_tuple(&self, pos: Point) -> AllNeighboorsTuple<T> {
+    pub fn all_neighboors_tuple(&self, pos: Point) -> AllNeighboorsTuple<'_, T> {
         use super::Direction::*;
 
         (
@@ -101,7 +108,7 @@ where
 }
 
 impl<T> Grid2dLine<'_, T> {
-    pub fn iter(&self) -> iterator::Grid2dCells<T> {
+    pub fn iter(&self) -> iterator::Grid2dCells<'_, T> {
         iterator::Grid2dCells::new(self.line)
     }
 }
@@ -0,0 +1,156 @@
+use std::ops::{Add, Sub};
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Point3d {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Point3d {
+    pub fn manhattan_distance(&self, other: &Point3d) -> u64 {
+        let delta = self - other;
+
+        delta.x.unsigned_abs() + delta.y.unsigned_abs() + delta.z.unsigned_abs()
+    }
+}
+
+impl From<(i64, i64, i64)> for Point3d {
+    fn from((x, y, z): (i64, i64, i64)) -> Self {
+        Point3d { x, y, z }
+    }
+}
+
+impl Add<Point3d> for Point3d {
+    type Output = Point3d;
+
+    fn add(self, rhs: Point3d) -> Self::Output {
+        Point3d {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl Sub<&Point3d> for &Point3d {
+    type Output = Point3d;
+
+    fn sub(self, rhs: &Point3d) -> Self::Output {
+        Point3d {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl std::fmt::Debug for Point3d {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {}, {}]", self.x, self.y, self.z)
+    }
+}
+
+pub const FACE_OFFSETS: [(i64, i64, i64); 6] = [
+    (-1, 0, 0),
+    (1, 0, 0),
+    (0, -1, 0),
+    (0, 1, 0),
+    (0, 0, -1),
+    (0, 0, 1),
+];
+
+#[derive(Clone)]
+pub struct Grid3d<T> {
+    values: Vec<Vec<Vec<T>>>,
+}
+
+impl<T> Grid3d<T> {
+    pub fn new(values: Vec<Vec<Vec<T>>>) -> Self {
+        Grid3d { values }
+    }
+
+    pub fn size(&self) -> (usize, usize, usize) {
+        let y = self.values.first().map(|plane| plane.len()).unwrap_or(0);
+        let z = self
+            .values
+            .first()
+            .and_then(|plane| plane.first())
+            .map(|line| line.len())
+            .unwrap_or(0);
+
+        (self.values.len(), y, z)
+    }
+
+    pub fn at(&self, pos: &Point3d) -> Option<&T> {
+        if pos.x < 0 || pos.y < 0 || pos.z < 0 {
+            return None;
+        }
+
+        self.values
+            .get(pos.x as usize)?
+            .get(pos.y as usize)?
+            .get(pos.z as usize)
+    }
+
+    pub fn at_mut(&mut self, pos: &Point3d) -> Option<&mut T> {
+        if pos.x < 0 || pos.y < 0 || pos.z < 0 {
+            return None;
+        }
+
+        self.values
+            .get_mut(pos.x as usize)?
+            .get_mut(pos.y as usize)?
+            .get_mut(pos.z as usize)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point3d, &T)> {
+        self.values.iter().enumerate().flat_map(|(x, plane)| {
+            plane.iter().enumerate().flat_map(move |(y, line)| {
+                line.iter()
+                    .enumerate()
+                    .map(move |(z, cell)| ((x as i64, y as i64, z as i64).into(), cell))
+            })
+        })
+    }
+
+    // 6-neighbourhood: cells sharing a face
+    pub fn face_neighboors(&self, pos: Point3d) -> impl Iterator<Item = (&T, Point3d)> {
+        FACE_OFFSETS.iter().filter_map(move |offset| {
+            let n_pos = pos + (*offset).into();
+
+            self.at(&n_pos).map(|cell| (cell, n_pos))
+        })
+    }
+
+    // 26-neighbourhood: cells sharing a face, an edge or a corner
+    pub fn all_neighboors(&self, pos: Point3d) -> impl Iterator<Item = (&T, Point3d)> {
+        (-1..=1)
+            .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| (x, y, z))))
+            .filter(|offset| *offset != (0, 0, 0))
+            .filter_map(move |offset| {
+                let n_pos = pos + offset.into();
+
+                self.at(&n_pos).map(|cell| (cell, n_pos))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighboors() {
+        let grid = Grid3d::new(vec![vec![vec![0; 3]; 3]; 3]);
+
+        assert_eq!(grid.face_neighboors((1, 1, 1).into()).count(), 6);
+        assert_eq!(grid.all_neighboors((1, 1, 1).into()).count(), 26);
+
+        assert_eq!(grid.face_neighboors((0, 0, 0).into()).count(), 3);
+        assert_eq!(grid.all_neighboors((0, 0, 0).into()).count(), 7);
+
+        assert!(grid.at(&(3, 0, 0).into()).is_none());
+        assert!(grid.at(&(0, -1, 0).into()).is_none());
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    collections::HashMap,
+    ops::{Add, AddAssign, Sub},
+};
+
+// Axial coordinates (pointy-top), the third cube coordinate is derived: s = -q - r
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Hex {
+    pub q: i64,
+    pub r: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HexDirection {
+    East,
+    NorthEast,
+    NorthWest,
+    West,
+    SouthWest,
+    SouthEast,
+}
+
+pub const HEX_DIRECTIONS: [HexDirection; 6] = [
+    HexDirection::East,
+    HexDirection::NorthEast,
+    HexDirection::NorthWest,
+    HexDirection::West,
+    HexDirection::SouthWest,
+    HexDirection::SouthEast,
+];
+
+impl Hex {
+    pub fn new(q: i64, r: i64) -> Self {
+        Hex { q, r }
+    }
+
+    pub fn from_cube(q: i64, r: i64, s: i64) -> Self {
+        debug_assert!(q + r + s == 0);
+
+        Hex { q, r }
+    }
+
+    pub fn s(&self) -> i64 {
+        -self.q - self.r
+    }
+
+    pub fn cube(&self) -> (i64, i64, i64) {
+        (self.q, self.r, self.s())
+    }
+
+    pub fn neighboor(&self, dir: HexDirection) -> Hex {
+        self + dir.into()
+    }
+
+    pub fn neighboors(&self) -> impl Iterator<Item = (Hex, HexDirection)> + '_ {
+        HEX_DIRECTIONS
+            .iter()
+            .map(|dir| (self.neighboor(*dir), *dir))
+    }
+
+    pub fn distance(&self, other: &Hex) -> u64 {
+        let delta = self - other;
+
+        (delta.q.unsigned_abs() + delta.r.unsigned_abs() + delta.s().unsigned_abs()) / 2
+    }
+}
+
+impl HexDirection {
+    pub fn turn_around(&self) -> Self {
+        match self {
+            Self::East => Self::West,
+            Self::NorthEast => Self::SouthWest,
+            Self::NorthWest => Self::SouthEast,
+            Self::West => Self::East,
+            Self::SouthWest => Self::NorthEast,
+            Self::SouthEast => Self::NorthWest,
+        }
+    }
+}
+
+impl From<(i64, i64)> for Hex {
+    fn from((q, r): (i64, i64)) -> Self {
+        Hex { q, r }
+    }
+}
+
+impl From<HexDirection> for Hex {
+    fn from(value: HexDirection) -> Self {
+        match value {
+            HexDirection::East => (1, 0).into(),
+            HexDirection::NorthEast => (1, -1).into(),
+            HexDirection::NorthWest => (0, -1).into(),
+            HexDirection::West => (-1, 0).into(),
+            HexDirection::SouthWest => (-1, 1).into(),
+            HexDirection::SouthEast => (0, 1).into(),
+        }
+    }
+}
+
+impl Add<Hex> for Hex {
+    type Output = Hex;
+
+    fn add(self, rhs: Hex) -> Self::Output {
+        Hex {
+            q: self.q + rhs.q,
+            r: self.r + rhs.r,
+        }
+    }
+}
+
+impl Add<Hex> for &Hex {
+    type Output = Hex;
+
+    fn add(self, rhs: Hex) -> Self::Output {
+        Hex {
+            q: self.q + rhs.q,
+            r: self.r + rhs.r,
+        }
+    }
+}
+
+impl Sub<&Hex> for &Hex {
+    type Output = Hex;
+
+    fn sub(self, rhs: &Hex) -> Self::Output {
+        Hex {
+            q: self.q - rhs.q,
+            r: self.r - rhs.r,
+        }
+    }
+}
+
+impl AddAssign<Hex> for Hex {
+    fn add_assign(&mut self, rhs: Hex) {
+        self.q += rhs.q;
+        self.r += rhs.r;
+    }
+}
+
+impl std::fmt::Debug for Hex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {}, {}]", self.q, self.r, self.s())
+    }
+}
+
+// Hex tilings are rarely rectangular, so cells are stored sparsely
+#[derive(Clone, Default)]
+pub struct HexGrid<T> {
+    cells: HashMap<Hex, T>,
+}
+
+impl<T> HexGrid<T> {
+    pub fn new() -> Self {
+        HexGrid {
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, pos: Hex, value: T) -> Option<T> {
+        self.cells.insert(pos, value)
+    }
+
+    pub fn at(&self, pos: &Hex) -> Option<&T> {
+        self.cells.get(pos)
+    }
+
+    pub fn at_mut(&mut self, pos: &Hex) -> Option<&mut T> {
+        self.cells.get_mut(pos)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Hex, &T)> {
+        self.cells.iter()
+    }
+
+    pub fn neighboors(&self, pos: Hex) -> impl Iterator<Item = (&T, Hex, HexDirection)> {
+        HEX_DIRECTIONS.iter().filter_map(move |dir| {
+            let n_pos = pos.neighboor(*dir);
+
+            self.at(&n_pos).map(|cell| (cell, n_pos, *dir))
+        })
+    }
+}
+
+impl<T> FromIterator<(Hex, T)> for HexGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Hex, T)>>(iter: I) -> Self {
+        HexGrid {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let origin = Hex::default();
+
+        assert_eq!(origin.distance(&origin), 0);
+        assert_eq!(origin.distance(&(3, 0).into()), 3);
+        assert_eq!(origin.distance(&(2, -3).into()), 3);
+        assert_eq!(origin.distance(&Hex::from_cube(1, 2, -3)), 3);
+
+        for (n, dir) in origin.neighboors() {
+            assert_eq!(origin.distance(&n), 1);
+            assert_eq!(n.neighboor(dir.turn_around()), origin);
+        }
+    }
+
+    #[test]
+    fn test_grid_neighboors() {
+        let grid: HexGrid<u8> = [((0, 0).into(), 0), ((1, 0).into(), 1), ((0, 1).into(), 2)]
+            .into_iter()
+            .collect();
+
+        let mut it = grid.neighboors((0, 0).into());
+
+        assert_eq!(it.next(), Some((&1, (1, 0).into(), HexDirection::East)));
+        assert_eq!(
+            it.next(),
+            Some((&2, (0, 1).into(), HexDirection::SouthEast))
+        );
+        assert_eq!(it.next(), None);
+    }
+}
@@ -0,0 +1,99 @@
+use std::{collections::HashMap, ops::Index};
+
+// Compact handle on an interned name, symbols are allocated sequentially from 0 so they can be
+// used directly as vector or bitset indexes
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Interner {
+    symbols: HashMap<String, Symbol>,
+    names: Vec<String>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(name) {
+            return *symbol;
+        }
+
+        let symbol = Symbol(self.names.len() as u32);
+
+        self.symbols.insert(name.to_string(), symbol);
+        self.names.push(name.to_string());
+
+        symbol
+    }
+
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.symbols.get(name).copied()
+    }
+
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.names[symbol.index()]
+    }
+
+    pub fn symbol(&self, index: usize) -> Symbol {
+        debug_assert!(index < self.names.len());
+
+        Symbol(index as u32)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol, &str)> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (Symbol(i as u32), name.as_str()))
+    }
+}
+
+impl Index<Symbol> for Interner {
+    type Output = str;
+
+    fn index(&self, symbol: Symbol) -> &Self::Output {
+        self.resolve(symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interner() {
+        let mut interner = Interner::new();
+
+        let kh = interner.intern("kh");
+        let tc = interner.intern("tc");
+
+        assert_eq!(interner.intern("kh"), kh);
+        assert_ne!(kh, tc);
+        assert_eq!(tc.index(), 1);
+        assert_eq!(&interner[tc], "tc");
+        assert_eq!(interner.get("tc"), Some(tc));
+        assert_eq!(interner.get("qp"), None);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(
+            interner.iter().collect::<Vec<_>>(),
+            vec![(kh, "kh"), (tc, "tc")]
+        );
+    }
+}
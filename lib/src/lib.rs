@@ -1,6 +1,24 @@
+pub mod bitset;
+pub mod cycle;
 pub mod debug;
+pub mod export;
+pub mod graph;
 pub mod grid;
+pub mod grid3d;
+pub mod hex;
+pub mod interner;
+pub mod linear;
+pub mod math;
+pub mod memo;
 pub mod point;
+pub mod pointset;
+pub mod rational;
+pub mod scan;
+pub mod search;
 
 pub use grid::*;
+pub use grid3d::*;
+pub use hex::*;
+pub use interner::*;
 pub use point::*;
+pub use pointset::*;
@@ -0,0 +1,483 @@
+use crate::{math::lcm, rational::Rational};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Solution {
+    None,
+    Unique(Vec<Rational>),
+    // Every `particular + sum(t_i * basis[i])` is a solution
+    Infinite {
+        particular: Vec<Rational>,
+        basis: Vec<Vec<Rational>>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CostError {
+    NoSolution,
+    // A value or the cost of the best solution doesn't fit an i64
+    Overflow,
+    // A free variable can grow without limit, either lowering the cost forever or making the
+    // enumeration endless
+    Unbounded,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct IntegerSolution {
+    pub values: Vec<i64>,
+    pub cost: i64,
+}
+
+// Reduced row echelon form of the augmented matrix [A | b]
+struct Echelon {
+    rows: Vec<Vec<Rational>>,
+    pivots: Vec<usize>,
+    n_vars: usize,
+    consistent: bool,
+}
+
+impl Echelon {
+    fn new(a: &[Vec<i64>], b: &[i64]) -> Self {
+        debug_assert!(a.len() == b.len());
+
+        let n_vars = a.first().map(|row| row.len()).unwrap_or(0);
+
+        let mut rows: Vec<Vec<Rational>> = a
+            .iter()
+            .zip(b)
+            .map(|(row, rhs)| {
+                row.iter()
+                    .chain(std::iter::once(rhs))
+                    .map(|v| Rational::from(*v))
+                    .collect()
+            })
+            .collect();
+
+        let mut pivots = vec![];
+        let mut pivot_row = 0;
+
+        for col in 0..n_vars {
+            let Some(found) = (pivot_row..rows.len()).find(|&r| !rows[r][col].is_zero()) else {
+                continue;
+            };
+
+            rows.swap(pivot_row, found);
+
+            let pivot = rows[pivot_row][col];
+            rows[pivot_row].iter_mut().for_each(|v| *v = *v / pivot);
+
+            let normalized = rows[pivot_row].clone();
+
+            for (r, row) in rows.iter_mut().enumerate() {
+                if r == pivot_row || row[col].is_zero() {
+                    continue;
+                }
+
+                let factor = row[col];
+
+                for (v, p) in row.iter_mut().zip(normalized.iter()).skip(col) {
+                    *v = *v - factor * *p;
+                }
+            }
+
+            pivots.push(col);
+            pivot_row += 1;
+        }
+
+        let consistent = rows[pivot_row..].iter().all(|row| row[n_vars].is_zero());
+        rows.truncate(pivot_row);
+
+        Echelon {
+            rows,
+            pivots,
+            n_vars,
+            consistent,
+        }
+    }
+
+    fn free_variables(&self) -> Vec<usize> {
+        (0..self.n_vars)
+            .filter(|col| !self.pivots.contains(col))
+            .collect()
+    }
+
+    // Value of the pivot variables once the free variables are set
+    fn pivot_values(&self, free: &[usize], free_values: &[i64]) -> Vec<Rational> {
+        self.rows
+            .iter()
+            .map(|row| {
+                free.iter()
+                    .zip(free_values)
+                    .fold(row[self.n_vars], |acc, (f, v)| {
+                        acc - row[*f] * Rational::from(*v)
+                    })
+            })
+            .collect()
+    }
+}
+
+// Solves A.x = b exactly
+pub fn solve(a: &[Vec<i64>], b: &[i64]) -> Solution {
+    let echelon = Echelon::new(a, b);
+
+    if !echelon.consistent {
+        return Solution::None;
+    }
+
+    let free = echelon.free_variables();
+
+    let mut particular = vec![Rational::ZERO; echelon.n_vars];
+
+    for (p, v) in echelon
+        .pivots
+        .iter()
+        .zip(echelon.pivot_values(&free, &vec![0; free.len()]))
+    {
+        particular[*p] = v;
+    }
+
+    if free.is_empty() {
+        return Solution::Unique(particular);
+    }
+
+    let basis = free
+        .iter()
+        .map(|f| {
+            let mut v = vec![Rational::ZERO; echelon.n_vars];
+            v[*f] = Rational::ONE;
+
+            for (row, p) in echelon.rows.iter().zip(echelon.pivots.iter()) {
+                v[*p] = -row[*f];
+            }
+
+            v
+        })
+        .collect();
+
+    Solution::Infinite { particular, basis }
+}
+
+// Minimises cost.x over the non-negative integer solutions of A.x = b (each value being at most
+// `max_value` if provided). With a single free variable the solutions lie on a line along which
+// the cost is linear, so only its two ends are checked. More free variables are enumerated, each
+// of them must then be bounded either by `max_value` or by an equation with only non-negative
+// coefficients.
+pub fn minimize_cost(
+    a: &[Vec<i64>],
+    b: &[i64],
+    cost: &[i64],
+    max_value: Option<i64>,
+) -> Result<IntegerSolution, CostError> {
+    let echelon = Echelon::new(a, b);
+
+    if !echelon.consistent {
+        return Err(CostError::NoSolution);
+    }
+
+    let free = echelon.free_variables();
+
+    if free.len() == 1 {
+        return minimize_on_line(&echelon, free[0], cost, max_value);
+    }
+
+    let bounds = free
+        .iter()
+        .map(|f| {
+            a.iter()
+                .zip(b)
+                .filter(|(row, _)| row[*f] > 0 && row.iter().all(|v| *v >= 0))
+                .map(|(row, rhs)| rhs / row[*f])
+                .chain(max_value)
+                .min()
+        })
+        .collect::<Option<Vec<i64>>>()
+        .ok_or(CostError::Unbounded)?;
+
+    let mut best: Option<IntegerSolution> = None;
+    let mut free_values = vec![0; free.len()];
+
+    loop {
+        if let Some(values) = integer_values(&echelon, &free, &free_values, max_value)? {
+            let solution = priced(values, cost)?;
+
+            if best.as_ref().is_none_or(|s| solution.cost < s.cost) {
+                best = Some(solution);
+            }
+        }
+
+        // Next assignment of the free variables (odometer style)
+        let Some(i) = (0..free.len()).find(|&i| free_values[i] < bounds[i]) else {
+            break;
+        };
+
+        free_values[i] += 1;
+        free_values[..i].iter_mut().for_each(|v| *v = 0);
+    }
+
+    best.ok_or(CostError::NoSolution)
+}
+
+// Pivot p is `row[n_vars] - row[f] * t` for the free variable t. Bounding every variable gives
+// an interval of t, in which the integer solutions repeat every lcm of the row[f] denominators.
+fn minimize_on_line(
+    echelon: &Echelon,
+    f: usize,
+    cost: &[i64],
+    max_value: Option<i64>,
+) -> Result<IntegerSolution, CostError> {
+    let ceil = |v: Rational| -(-v).floor();
+
+    let mut low: i128 = 0;
+    let mut high: Option<i128> = max_value.map(i128::from);
+    let mut step: i64 = 1;
+
+    for row in echelon.rows.iter() {
+        let (c, r) = (row[echelon.n_vars], row[f]);
+
+        if r.is_zero() {
+            continue;
+        }
+
+        step = i64::try_from(r.denom())
+            .ok()
+            .and_then(|denom| lcm(step, denom))
+            .ok_or(CostError::Overflow)?;
+
+        // c - r * t >= 0 and, with a maximum, c - r * t <= max
+        let at_zero = c / r;
+        let at_max = max_value.map(|max| (c - Rational::from(max)) / r);
+
+        if r > Rational::ZERO {
+            high = Some(high.map_or(at_zero.floor(), |h| h.min(at_zero.floor())));
+            low = at_max.map_or(low, |v| low.max(ceil(v)));
+        } else {
+            low = low.max(ceil(at_zero));
+            high = at_max.map_or(high, |v| Some(high.map_or(v.floor(), |h| h.min(v.floor()))));
+        }
+    }
+
+    // Values at t, if they're a solution. Past i64::MAX t itself overflows.
+    let values = |t: i128| -> Result<Option<Vec<i64>>, CostError> {
+        let t = i64::try_from(t).map_err(|_| CostError::Overflow)?;
+
+        integer_values(echelon, &[f], &[t], max_value)
+    };
+
+    let last_try = high.map_or(low + step as i128 - 1, |h| h.min(low + step as i128 - 1));
+    let mut first = None;
+
+    for t in low..=last_try {
+        if let Some(values) = values(t)? {
+            first = Some((t, values));
+            break;
+        }
+    }
+
+    let (first, lowest) = first.ok_or(CostError::NoSolution)?;
+
+    // Cost change when t grows by one
+    let slope = echelon
+        .rows
+        .iter()
+        .zip(echelon.pivots.iter())
+        .fold(Rational::from(cost[f]), |acc, (row, p)| {
+            acc - Rational::from(cost[*p]) * row[f]
+        });
+
+    // Only the chosen end is priced, the other one may not even have a cost fitting an i64
+    match (slope < Rational::ZERO, high) {
+        (false, _) => priced(lowest, cost),
+        (true, None) => Err(CostError::Unbounded),
+        (true, Some(high)) => {
+            let step = step as i128;
+            let last = first + (high - first) / step * step;
+
+            priced(values(last)?.ok_or(CostError::NoSolution)?, cost)
+        }
+    }
+}
+
+fn priced(values: Vec<i64>, cost: &[i64]) -> Result<IntegerSolution, CostError> {
+    let cost = values
+        .iter()
+        .zip(cost)
+        .try_fold(0i64, |total, (v, c)| total.checked_add(v.checked_mul(*c)?))
+        .ok_or(CostError::Overflow)?;
+
+    Ok(IntegerSolution { values, cost })
+}
+
+// None if the values aren't a non-negative integer solution
+fn integer_values(
+    echelon: &Echelon,
+    free: &[usize],
+    free_values: &[i64],
+    max_value: Option<i64>,
+) -> Result<Option<Vec<i64>>, CostError> {
+    let mut values = vec![0; echelon.n_vars];
+
+    for (f, v) in free.iter().zip(free_values) {
+        values[*f] = *v;
+    }
+
+    for (p, v) in echelon
+        .pivots
+        .iter()
+        .zip(echelon.pivot_values(free, free_values))
+    {
+        let Some(v) = v.to_integer() else {
+            return Ok(None);
+        };
+
+        if v < 0 || max_value.is_some_and(|max| v > max as i128) {
+            return Ok(None);
+        }
+
+        values[*p] = i64::try_from(v).map_err(|_| CostError::Overflow)?;
+    }
+
+    Ok(Some(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        assert_eq!(
+            solve(&[vec![94, 22], vec![34, 67]], &[8400, 5400]),
+            Solution::Unique(vec![80i64.into(), 40i64.into()])
+        );
+
+        assert_eq!(solve(&[vec![1, 1], vec![2, 2]], &[1, 3]), Solution::None);
+
+        let Solution::Infinite { particular, basis } = solve(&[vec![1, 2, 3]], &[6]) else {
+            panic!("Expected infinite solutions");
+        };
+
+        assert_eq!(particular, vec![6i64.into(), 0i64.into(), 0i64.into()]);
+        assert_eq!(basis.len(), 2);
+    }
+
+    #[test]
+    fn test_minimize_cost() {
+        // x + 2y + 3z = 6: (6, 0, 0) costs 6, (0, 0, 2) costs 2, (0, 3, 0) costs 3
+        assert_eq!(
+            minimize_cost(&[vec![1, 2, 3]], &[6], &[1, 1, 1], None),
+            Ok(IntegerSolution {
+                values: vec![0, 0, 2],
+                cost: 2
+            })
+        );
+
+        assert_eq!(
+            minimize_cost(&[vec![1, 2, 3]], &[6], &[1, 1, 5], Some(2)),
+            Ok(IntegerSolution {
+                values: vec![2, 2, 0],
+                cost: 4
+            })
+        );
+
+        assert_eq!(
+            minimize_cost(&[vec![2, 4]], &[3], &[1, 1], None),
+            Err(CostError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn test_minimize_cost_on_line() {
+        // Collinear columns: x + 2y = 10^13
+        let (a, b) = (
+            vec![vec![1, 2], vec![2, 4]],
+            [10_000_000_000_000, 20_000_000_000_000],
+        );
+
+        assert_eq!(
+            minimize_cost(&a, &b, &[3, 1], None),
+            Ok(IntegerSolution {
+                values: vec![0, 5_000_000_000_000],
+                cost: 5_000_000_000_000
+            })
+        );
+        assert_eq!(
+            minimize_cost(&a, &b, &[1, 3], None),
+            Ok(IntegerSolution {
+                values: vec![10_000_000_000_000, 0],
+                cost: 10_000_000_000_000
+            })
+        );
+
+        // 3x + 5y = 31: (2, 5), (7, 2), steps of 5 on x
+        assert_eq!(
+            minimize_cost(&[vec![3, 5]], &[31], &[-1, 0], None),
+            Ok(IntegerSolution {
+                values: vec![7, 2],
+                cost: -7
+            })
+        );
+        assert_eq!(
+            minimize_cost(&[vec![3, 5]], &[31], &[1, 0], Some(6)),
+            Ok(IntegerSolution {
+                values: vec![2, 5],
+                cost: 2
+            })
+        );
+        assert_eq!(
+            minimize_cost(&[vec![3, 5]], &[31], &[1, 0], Some(4)),
+            Err(CostError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn test_minimize_cost_overflow() {
+        // x = i64::MAX, costing twice as much
+        assert_eq!(
+            minimize_cost(&[vec![1]], &[i64::MAX], &[2], None),
+            Err(CostError::Overflow)
+        );
+
+        // x + y = i64::MAX, the cheap end fits even though the other one doesn't
+        assert_eq!(
+            minimize_cost(&[vec![1, 1]], &[i64::MAX], &[2, 1], None),
+            Ok(IntegerSolution {
+                values: vec![0, i64::MAX],
+                cost: i64::MAX
+            })
+        );
+        assert_eq!(
+            minimize_cost(&[vec![1, 1]], &[i64::MAX], &[2, 3], None),
+            Err(CostError::Overflow)
+        );
+
+        // x - y = i64::MAX: x doesn't fit once y > 0, so y = 0 is the only solution
+        assert_eq!(
+            minimize_cost(&[vec![1, -1]], &[i64::MAX], &[1, 1], None),
+            Ok(IntegerSolution {
+                values: vec![i64::MAX, 0],
+                cost: i64::MAX
+            })
+        );
+    }
+
+    #[test]
+    fn test_minimize_cost_unbounded() {
+        // x = y, the cost keeps going down
+        assert_eq!(
+            minimize_cost(&[vec![1, -1]], &[0], &[-1, 0], None),
+            Err(CostError::Unbounded)
+        );
+        assert_eq!(
+            minimize_cost(&[vec![1, -1]], &[0], &[1, 0], None),
+            Ok(IntegerSolution {
+                values: vec![0, 0],
+                cost: 0
+            })
+        );
+
+        // Two free variables and nothing bounding them
+        assert_eq!(
+            minimize_cost(&[vec![1, -1, -1]], &[0], &[1, 1, 1], None),
+            Err(CostError::Unbounded)
+        );
+    }
+}
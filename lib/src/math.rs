@@ -0,0 +1,246 @@
+// Unsigned as gcd(i64::MIN, 0) is 2^63
+pub fn gcd(a: i64, b: i64) -> u64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+// None if it doesn't fit an i64
+pub fn lcm(a: i64, b: i64) -> Option<i64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+
+    (a.unsigned_abs() / gcd(a, b))
+        .checked_mul(b.unsigned_abs())
+        .and_then(|lcm| i64::try_from(lcm).ok())
+}
+
+// Returns (g, x, y) such that a * x + b * y = g = gcd(a, b)
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (g, x, y) = extended_gcd_i128(a as i128, b as i128);
+
+    (g as i64, x as i64, y as i64)
+}
+
+fn extended_gcd_i128(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1, 0);
+    let (mut old_t, mut t) = (0, 1);
+
+    while r != 0 {
+        let q = old_r / r;
+
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+        (old_t, t) = (t, old_t - q * t);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+pub fn mod_pow(base: u64, exp: u64, modulus: u64) -> u64 {
+    if modulus == 1 {
+        return 0;
+    }
+
+    let modulus = modulus as u128;
+    let mut base = base as u128 % modulus;
+    let mut exp = exp;
+    let mut result = 1u128;
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+
+    result as u64
+}
+
+// None if a and the modulus aren't coprime, or the modulus isn't positive
+pub fn mod_inv(a: i64, modulus: i64) -> Option<i64> {
+    if modulus <= 0 {
+        return None;
+    }
+
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus), modulus);
+
+    if g != 1 {
+        return None;
+    }
+
+    Some(x.rem_euclid(modulus))
+}
+
+// Solves x = r_i (mod m_i) for every (r_i, m_i), moduli don't need to be coprime.
+// Returns (x, lcm of moduli) with 0 <= x < lcm, or None if the congruences conflict, a modulus
+// isn't positive or the lcm doesn't fit an i64.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut result: (i128, i128) = (0, 1);
+
+    for &(r, m) in congruences {
+        if m <= 0 {
+            return None;
+        }
+
+        let (r, m) = ((r as i128).rem_euclid(m as i128), m as i128);
+        let (x, n) = result;
+
+        let (g, p, _) = extended_gcd_i128(n, m);
+
+        if (r - x) % g != 0 {
+            return None;
+        }
+
+        // Both below 2^63 so the products fit an i128
+        let lcm = n / g * m;
+        let k = ((r - x) / g % (m / g)) * p % (m / g);
+
+        if lcm > i64::MAX as i128 {
+            return None;
+        }
+
+        result = ((x + n * k).rem_euclid(lcm), lcm);
+    }
+
+    Some((result.0 as i64, result.1 as i64))
+}
+
+// Floor of the logarithm of n in `base`: ilog(1000, 10) == Some(3). None if n is 0 or base < 2.
+pub fn ilog(n: u64, base: u64) -> Option<u32> {
+    if base < 2 {
+        return None;
+    }
+
+    n.checked_ilog(base)
+}
+
+pub fn isqrt(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+
+    let mut x = (n as f64).sqrt() as u64;
+
+    // Float approximation can be off by one on large values
+    while x.checked_mul(x).is_none_or(|sq| sq > n) {
+        x -= 1;
+    }
+
+    while (x + 1).checked_mul(x + 1).is_some_and(|sq| sq <= n) {
+        x += 1;
+    }
+
+    x
+}
+
+pub fn n_digit(n: u64) -> u32 {
+    if n == 0 {
+        return 1;
+    }
+
+    n.ilog10() + 1
+}
+
+// Split a number with an even number of digits in two halves: 123456 -> (123, 456)
+pub fn split_int(n: u64) -> (u64, u64) {
+    let half = 10u64.pow(n_digit(n) / 2);
+
+    (n / half, n % half)
+}
+
+pub fn digits(n: u64) -> Vec<u8> {
+    let mut digits: Vec<u8> = Vec::with_capacity(n_digit(n) as usize);
+    let mut n = n;
+
+    loop {
+        digits.push((n % 10) as u8);
+        n /= 10;
+
+        if n == 0 {
+            break;
+        }
+    }
+
+    digits.reverse();
+    digits
+}
+
+pub fn from_digits(digits: &[u8]) -> u64 {
+    digits.iter().fold(0, |acc, d| acc * 10 + *d as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(gcd(i64::MIN, 0), 1 << 63);
+        assert_eq!(gcd(i64::MIN, 6), 2);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(-4, 6), Some(12));
+        assert_eq!(lcm(0, 6), Some(0));
+        assert_eq!(lcm(i64::MAX, 2), None);
+        assert_eq!(lcm(i64::MIN, 1), None);
+
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(mod_pow(2, 10, 1000), 24);
+        assert_eq!(mod_pow(3, 200, 1_000_000_007), 136_318_165);
+        assert_eq!(mod_inv(3, 11), Some(4));
+        assert_eq!(mod_inv(2, 4), None);
+        assert_eq!(mod_inv(3, 0), None);
+        assert_eq!(mod_inv(3, -7), None);
+
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(1, 0)]), None);
+        assert_eq!(
+            crt(&[(5, 2_147_483_647), (7, 2_147_483_629)]),
+            Some((4_099_276_422_885_467_044, 4_611_685_975_477_714_963))
+        );
+        // The lcm doesn't fit an i64
+        assert_eq!(crt(&[(1, 4_000_000_007), (2, 4_000_000_009)]), None);
+    }
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u64::MAX), 4_294_967_295);
+
+        assert_eq!(ilog(1000, 10), Some(3));
+        assert_eq!(ilog(999, 10), Some(2));
+        assert_eq!(ilog(1 << 40, 2), Some(40));
+        assert_eq!(ilog(0, 10), None);
+        assert_eq!(ilog(5, 1), None);
+
+        assert_eq!(n_digit(0), 1);
+        assert_eq!(n_digit(1234), 4);
+        assert_eq!(split_int(123001), (123, 1));
+        assert_eq!(digits(907), vec![9, 0, 7]);
+        assert_eq!(from_digits(&[9, 0, 7]), 907);
+    }
+}
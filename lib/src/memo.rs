@@ -0,0 +1,174 @@
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+// Cache for recursive solvers. The cache is kept between calls, so the same Memo can be reused
+// for every input sharing the same context (and must be cleared when that context changes).
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    insertion_order: VecDeque<K>,
+    capacity: Option<usize>,
+    stats: MemoStats,
+}
+
+impl<K, V> Memo<K, V>
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Memo {
+            cache: HashMap::new(),
+            insertion_order: VecDeque::new(),
+            capacity: None,
+            stats: MemoStats::default(),
+        }
+    }
+
+    // Oldest entries are evicted first once the capacity is reached
+    pub fn with_capacity_limit(capacity: usize) -> Self {
+        Memo {
+            capacity: Some(capacity),
+            ..Self::new()
+        }
+    }
+
+    pub fn get<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let value = self.cache.get(key).cloned();
+
+        if value.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+
+        value
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.cache.insert(key.clone(), value).is_some() {
+            return;
+        }
+
+        if self.capacity.is_none() {
+            return;
+        }
+
+        self.insertion_order.push_back(key);
+
+        while self.capacity.is_some_and(|c| self.cache.len() > c) {
+            if let Some(oldest) = self.insertion_order.pop_front() {
+                self.cache.remove(&oldest);
+                self.stats.evictions += 1;
+            }
+        }
+    }
+
+    // Returns the cached value or computes it with f, which receives the memo back to recurse
+    pub fn get_or_compute<Q, F>(&mut self, key: &Q, f: &F) -> V
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+        F: Fn(&mut Self, &Q) -> V,
+    {
+        if let Some(value) = self.get(key) {
+            return value;
+        }
+
+        let value = f(self, key);
+        self.insert(key.to_owned(), value.clone());
+
+        value
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.insertion_order.clear();
+        self.stats = MemoStats::default();
+    }
+}
+
+impl<K, V> Default for Memo<K, V>
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fibonacci(memo: &mut Memo<u64, u64>, n: &u64) -> u64 {
+        if *n < 2 {
+            return *n;
+        }
+
+        memo.get_or_compute(&(n - 1), &fibonacci) + memo.get_or_compute(&(n - 2), &fibonacci)
+    }
+
+    #[test]
+    fn test_memo() {
+        let mut memo = Memo::new();
+
+        assert_eq!(memo.get_or_compute(&90, &fibonacci), 2880067194370816120);
+        assert_eq!(memo.stats().misses, 91);
+
+        // Reuse across calls
+        assert_eq!(memo.get_or_compute(&80, &fibonacci), 23416728348467685);
+        assert_eq!(memo.stats().misses, 91);
+
+        memo.clear();
+        assert!(memo.is_empty());
+        assert_eq!(memo.stats(), MemoStats::default());
+    }
+
+    #[test]
+    fn test_capacity_limit() {
+        let mut memo = Memo::with_capacity_limit(2);
+
+        memo.insert("a", 1);
+        memo.insert("b", 2);
+        memo.insert("c", 3);
+
+        assert_eq!(memo.len(), 2);
+        assert_eq!(memo.get("a"), None);
+        assert_eq!(memo.get("c"), Some(3));
+        assert_eq!(
+            memo.stats(),
+            MemoStats {
+                hits: 1,
+                misses: 1,
+                evictions: 1
+            }
+        );
+    }
+}
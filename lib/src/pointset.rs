@@ -0,0 +1,264 @@
+use std::ops::Index;
+
+use crate::{bitset::BitSet, Grid2d, Point};
+
+// Dense replacements for HashSet<Point> / HashMap<Point, T> over a bounded grid: points are
+// indexed as x * width + y, so lookups never hash. Points outside of the grid are never
+// contained, inserting one fails.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds(pub Point);
+
+impl std::fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Point {:?} out of bounds", self.0)
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct PointSet {
+    size: (usize, usize),
+    bits: BitSet,
+}
+
+impl PointSet {
+    pub fn new(size: (usize, usize)) -> Self {
+        PointSet {
+            size,
+            bits: BitSet::with_capacity(size.0 * size.1),
+        }
+    }
+
+    pub fn for_grid<T>(grid: &Grid2d<T>) -> Self {
+        Self::new(grid.size())
+    }
+
+    // Ok(false) if the point was already there
+    pub fn insert(&mut self, point: Point) -> Result<bool, OutOfBounds> {
+        let index = point_index(self.size, &point).ok_or(OutOfBounds(point))?;
+
+        Ok(self.bits.insert(index))
+    }
+
+    pub fn remove(&mut self, point: &Point) -> bool {
+        point_index(self.size, point).is_some_and(|i| self.bits.remove(i))
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        point_index(self.size, point).is_some_and(|i| self.bits.contains(i))
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        self.bits.iter().map(|i| index_point(self.size, i))
+    }
+}
+
+impl std::fmt::Debug for PointSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[derive(Clone)]
+pub struct PointMap<T> {
+    size: (usize, usize),
+    values: Vec<Option<T>>,
+    len: usize,
+}
+
+impl<T> PointMap<T> {
+    pub fn new(size: (usize, usize)) -> Self {
+        PointMap {
+            size,
+            values: std::iter::repeat_with(|| None)
+                .take(size.0 * size.1)
+                .collect(),
+            len: 0,
+        }
+    }
+
+    pub fn for_grid<U>(grid: &Grid2d<U>) -> Self {
+        Self::new(grid.size())
+    }
+
+    // Ok with the previous value
+    pub fn insert(&mut self, point: Point, value: T) -> Result<Option<T>, OutOfBounds> {
+        let index = point_index(self.size, &point).ok_or(OutOfBounds(point))?;
+        let previous = self.values[index].replace(value);
+
+        if previous.is_none() {
+            self.len += 1;
+        }
+
+        Ok(previous)
+    }
+
+    pub fn remove(&mut self, point: &Point) -> Option<T> {
+        let previous = self.values[point_index(self.size, point)?].take();
+
+        if previous.is_some() {
+            self.len -= 1;
+        }
+
+        previous
+    }
+
+    pub fn get(&self, point: &Point) -> Option<&T> {
+        self.values[point_index(self.size, point)?].as_ref()
+    }
+
+    pub fn get_mut(&mut self, point: &Point) -> Option<&mut T> {
+        self.values[point_index(self.size, point)?].as_mut()
+    }
+
+    pub fn get_or_insert_with(
+        &mut self,
+        point: Point,
+        f: impl FnOnce() -> T,
+    ) -> Result<&mut T, OutOfBounds> {
+        let index = point_index(self.size, &point).ok_or(OutOfBounds(point))?;
+
+        if self.values[index].is_none() {
+            self.len += 1;
+        }
+
+        Ok(self.values[index].get_or_insert_with(f))
+    }
+
+    pub fn contains_key(&self, point: &Point) -> bool {
+        self.get(point).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.values.iter_mut().for_each(|v| *v = None);
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| v.as_ref().map(|v| (index_point(self.size, i), v)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = Point> + '_ {
+        self.iter().map(|(p, _)| p)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> + '_ {
+        self.values.iter().flatten()
+    }
+}
+
+impl<T> Index<&Point> for PointMap<T> {
+    type Output = T;
+
+    fn index(&self, point: &Point) -> &Self::Output {
+        self.get(point).expect("Point not in map")
+    }
+}
+
+impl<T> std::fmt::Debug for PointMap<T>
+where
+    T: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+fn point_index((height, width): (usize, usize), point: &Point) -> Option<usize> {
+    if point.x < 0 || point.y < 0 || point.x as usize >= height || point.y as usize >= width {
+        return None;
+    }
+
+    Some(point.x as usize * width + point.y as usize)
+}
+
+fn index_point((_, width): (usize, usize), index: usize) -> Point {
+    ((index / width) as i64, (index % width) as i64).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_set() {
+        let mut set = PointSet::new((3, 4));
+
+        assert_eq!(set.insert((2, 3).into()), Ok(true));
+        assert_eq!(set.insert((2, 3).into()), Ok(false));
+        assert_eq!(set.insert((0, 1).into()), Ok(true));
+
+        assert!(set.contains(&(2, 3).into()));
+        assert!(!set.contains(&(3, 2).into()));
+        assert!(!set.contains(&(-1, 0).into()));
+        assert_eq!(set.len(), 2);
+        assert_eq!(
+            set.iter().collect::<Vec<Point>>(),
+            vec![(0, 1).into(), (2, 3).into()]
+        );
+    }
+
+    #[test]
+    fn test_point_map() {
+        let mut map = PointMap::new((2, 2));
+
+        assert_eq!(map.insert((1, 0).into(), 5), Ok(None));
+        assert_eq!(map.insert((1, 0).into(), 6), Ok(Some(5)));
+        *map.get_or_insert_with((0, 1).into(), || 0).unwrap() += 1;
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map[&(1, 0).into()], 6);
+        assert_eq!(map.get(&(0, 1).into()), Some(&1));
+        assert_eq!(map.get(&(5, 5).into()), None);
+        assert_eq!(map.remove(&(1, 0).into()), Some(6));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let mut set = PointSet::new((2, 3));
+
+        for point in [(-1, 0), (0, -1), (2, 0), (0, 3), (i64::MIN, i64::MAX)] {
+            let point: Point = point.into();
+
+            assert_eq!(set.insert(point), Err(OutOfBounds(point)));
+            assert!(!set.contains(&point));
+            assert!(!set.remove(&point));
+        }
+
+        assert!(set.is_empty());
+
+        let mut map = PointMap::new((2, 3));
+        let point = (0, 3).into();
+
+        assert_eq!(map.insert(point, 1), Err(OutOfBounds(point)));
+        assert_eq!(map.get_or_insert_with(point, || 1), Err(OutOfBounds(point)));
+        assert_eq!(map.remove(&point), None);
+        assert!(map.is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanError {
+    // Byte offset in the scanned input
+    pub position: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at {})", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+// Typed pattern matching, each `{}` of the pattern is parsed with the matching type:
+// `scan!("p=0,4 v=3,-3", "p={},{} v={},{}", i64, i64, i64, i64) == Ok((0, 4, 3, -3))`
+#[macro_export]
+macro_rules! scan {
+    ($input:expr, $pattern:expr, $($t:ty),+ $(,)?) => {{
+        let n_fields = [$(stringify!($t)),+].len();
+
+        $crate::scan::scan_fields($input, $pattern, n_fields).and_then(|fields| {
+            let mut fields = fields.into_iter();
+
+            Ok(($($crate::scan::parse_field::<$t>(fields.next().unwrap())?,)+))
+        })
+    }};
+}
+
+// Returns the (position, text) of each `{}` of the pattern. A placeholder captures everything up
+// to the next literal part of the pattern (or the end of the input for the last one).
+pub fn scan_fields<'a>(
+    input: &'a str,
+    pattern: &str,
+    n_fields: usize,
+) -> Result<Vec<(usize, &'a str)>, ScanError> {
+    let literals: Vec<&str> = pattern.split("{}").collect();
+
+    if literals.len() - 1 != n_fields {
+        panic!(
+            "Pattern {:?} has {} fields, {} types given",
+            pattern,
+            literals.len() - 1,
+            n_fields
+        );
+    }
+
+    let mut position = expect_literal(input, 0, literals[0])?;
+    let mut fields = Vec::with_capacity(n_fields);
+
+    for (i, literal) in literals.iter().enumerate().skip(1) {
+        let is_last = i == literals.len() - 1;
+
+        let end = if is_last && literal.is_empty() {
+            input.len()
+        } else if literal.is_empty() {
+            panic!("Pattern {:?} has two consecutive fields", pattern);
+        } else {
+            input[position..]
+                .find(literal)
+                .map(|offset| position + offset)
+                .ok_or_else(|| ScanError {
+                    position,
+                    message: format!("Expected {:?}", literal),
+                })?
+        };
+
+        fields.push((position, &input[position..end]));
+        position = expect_literal(input, end, literal)?;
+    }
+
+    if position != input.len() {
+        return Err(ScanError {
+            position,
+            message: format!("Unexpected trailing input {:?}", &input[position..]),
+        });
+    }
+
+    Ok(fields)
+}
+
+pub fn parse_field<T: FromStr>((position, text): (usize, &str)) -> Result<T, ScanError> {
+    text.parse().map_err(|_| ScanError {
+        position,
+        message: format!(
+            "Invalid value {:?} for {}",
+            text,
+            std::any::type_name::<T>()
+        ),
+    })
+}
+
+fn expect_literal(input: &str, position: usize, literal: &str) -> Result<usize, ScanError> {
+    if !input[position..].starts_with(literal) {
+        return Err(ScanError {
+            position,
+            message: format!("Expected {:?}", literal),
+        });
+    }
+
+    Ok(position + literal.len())
+}
+
+// All the signed integers of a string: "p=0,4 v=3,-3" -> [0, 4, 3, -3]. Fails on the first one
+// not fitting an i64.
+pub fn ints(input: &str) -> Result<Vec<i64>, ScanError> {
+    let bytes = input.as_bytes();
+    let mut result = vec![];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let negative = bytes[i] == b'-'
+            && bytes.get(i + 1).is_some_and(|b| b.is_ascii_digit())
+            && (i == 0 || !bytes[i - 1].is_ascii_digit());
+
+        if !negative && !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        i += 1;
+
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+
+        result.push(parse_field((start, &input[start..i]))?);
+    }
+
+    Ok(result)
+}
+
+// Blocks separated by blank lines
+pub fn sections(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .split("\n\n")
+        .map(|section| section.trim_matches('\n'))
+        .filter(|section| !section.is_empty())
+}
+
+// `key: value` lines
+pub fn records(input: &str) -> Result<Vec<(&str, &str)>, ScanError> {
+    lines_with_position(input)
+        .map(|(position, line)| {
+            line.split_once(':')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| ScanError {
+                    position,
+                    message: format!("Expected `key: value`, got {:?}", line),
+                })
+        })
+        .collect()
+}
+
+// `a<separator>b` lines, e.g. `47|53`
+pub fn pairs<T: FromStr>(input: &str, separator: &str) -> Result<Vec<(T, T)>, ScanError> {
+    lines_with_position(input)
+        .map(|(position, line)| {
+            let Some((a, b)) = line.split_once(separator) else {
+                return Err(ScanError {
+                    position,
+                    message: format!("Expected {:?} in {:?}", separator, line),
+                });
+            };
+
+            Ok((
+                parse_field((position, a))?,
+                parse_field((position + a.len() + separator.len(), b))?,
+            ))
+        })
+        .collect()
+}
+
+fn lines_with_position(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .lines()
+        .map(move |line| (line.as_ptr() as usize - input.as_ptr() as usize, line))
+        .filter(|(_, line)| !line.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan() {
+        assert_eq!(
+            scan!("p=0,4 v=3,-3", "p={},{} v={},{}", i64, i64, i64, i64),
+            Ok((0, 4, 3, -3))
+        );
+        assert_eq!(
+            scan!(
+                "Button A: X+94, Y+34",
+                "Button {}: X{}, Y{}",
+                char,
+                i64,
+                i64
+            ),
+            Ok(('A', 94, 34))
+        );
+
+        assert_eq!(
+            scan!("p=0;4", "p={},{}", i64, i64),
+            Err(ScanError {
+                position: 2,
+                message: "Expected \",\"".to_string()
+            })
+        );
+        assert_eq!(scan!("p=a,4", "p={},{}", i64, i64).unwrap_err().position, 2);
+        assert_eq!(scan!("x=1,4", "p={},{}", i64, i64).unwrap_err().position, 0);
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(
+            ints("p=0,4 v=3,-3 x-1 1-2"),
+            Ok(vec![0, 4, 3, -3, -1, 1, 2])
+        );
+        assert_eq!(ints("").map(|ints| ints.len()), Ok(0));
+        assert_eq!(
+            ints("1 99999999999999999999 -2").map_err(|e| e.position),
+            Err(2)
+        );
+        assert_eq!(
+            ints("-9223372036854775808 -9223372036854775809").map_err(|e| e.position),
+            Err(21)
+        );
+
+        let input = "Register A: 729\nRegister B: 0\n\nProgram: 0,1\n";
+        let blocks: Vec<&str> = sections(input).collect();
+
+        assert_eq!(
+            blocks,
+            vec!["Register A: 729\nRegister B: 0", "Program: 0,1"]
+        );
+        assert_eq!(
+            records(blocks[0]),
+            Ok(vec![("Register A", "729"), ("Register B", "0")])
+        );
+
+        assert_eq!(
+            pairs::<u32>("47|53\n97|13", "|"),
+            Ok(vec![(47, 53), (97, 13)])
+        );
+        assert_eq!(pairs::<u32>("47|53\n97|x", "|").unwrap_err().position, 9);
+    }
+}
@@ -0,0 +1,138 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+// Searches are agnostic of the grid topology: the caller provides the neighboors of a
+// position (e.g. `Grid2d::cardinal_neighboors`, `HexGrid::neighboors`,
+// `Grid3d::face_neighboors` or `Grid3d::all_neighboors`) filtered on walkable cells.
+
+pub fn bfs<P, I, F>(start: P, mut neighboors: F) -> HashMap<P, u64>
+where
+    P: Copy + Eq + Hash,
+    I: IntoIterator<Item = P>,
+    F: FnMut(P) -> I,
+{
+    let mut distances = HashMap::new();
+    let mut to_visit = VecDeque::new();
+
+    distances.insert(start, 0);
+    to_visit.push_back(start);
+
+    while let Some(pos) = to_visit.pop_front() {
+        let distance = distances[&pos];
+
+        for n_pos in neighboors(pos) {
+            if distances.contains_key(&n_pos) {
+                continue;
+            }
+
+            distances.insert(n_pos, distance + 1);
+            to_visit.push_back(n_pos);
+        }
+    }
+
+    distances
+}
+
+pub fn shortest_path<P, I, F>(start: P, end: P, mut neighboors: F) -> Option<Vec<P>>
+where
+    P: Copy + Eq + Hash,
+    I: IntoIterator<Item = P>,
+    F: FnMut(P) -> I,
+{
+    let mut reverse_path = HashMap::new();
+    let mut to_visit = VecDeque::new();
+
+    reverse_path.insert(start, start);
+    to_visit.push_back(start);
+
+    while let Some(pos) = to_visit.pop_front() {
+        if pos == end {
+            let mut path = vec![end];
+            let mut current = end;
+
+            while current != start {
+                current = reverse_path[&current];
+                path.push(current);
+            }
+
+            path.reverse();
+
+            return Some(path);
+        }
+
+        for n_pos in neighboors(pos) {
+            if reverse_path.contains_key(&n_pos) {
+                continue;
+            }
+
+            reverse_path.insert(n_pos, pos);
+            to_visit.push_back(n_pos);
+        }
+    }
+
+    None
+}
+
+pub fn flood_fill<P, I, F>(start: P, neighboors: F) -> HashSet<P>
+where
+    P: Copy + Eq + Hash,
+    I: IntoIterator<Item = P>,
+    F: FnMut(P) -> I,
+{
+    bfs(start, neighboors).into_keys().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Grid2d, Grid3d, HexGrid, Point};
+
+    use super::*;
+
+    #[test]
+    fn test_search_on_all_grids() {
+        let grid = Grid2d::new(vec![
+            vec!['.', '.', '.'],
+            vec!['#', '#', '.'],
+            vec!['.', '.', '.'],
+        ]);
+
+        let free = |pos: Point| {
+            grid.cardinal_neighboors(pos)
+                .filter(|(c, _, _)| **c == '.')
+                .map(|(_, p, _)| p)
+        };
+
+        assert_eq!(bfs((0, 0).into(), free)[&(2, 0).into()], 6);
+        assert_eq!(
+            shortest_path((0, 0).into(), (2, 2).into(), free).map(|p| p.len()),
+            Some(5)
+        );
+        assert_eq!(flood_fill((0, 0).into(), free).len(), 7);
+
+        let hex_grid: HexGrid<()> = (0..5).map(|q| ((q, 0).into(), ())).collect();
+        let distances = bfs((0, 0).into(), |pos| {
+            hex_grid
+                .neighboors(pos)
+                .map(|(_, p, _)| p)
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(distances[&(4, 0).into()], 4);
+
+        let cube = Grid3d::new(vec![vec![vec![(); 3]; 3]; 3]);
+
+        let face = bfs((0, 0, 0).into(), |pos| {
+            cube.face_neighboors(pos)
+                .map(|(_, p)| p)
+                .collect::<Vec<_>>()
+        });
+        let all = bfs((0, 0, 0).into(), |pos| {
+            cube.all_neighboors(pos).map(|(_, p)| p).collect::<Vec<_>>()
+        });
+
+        assert_eq!(face[&(2, 2, 2).into()], 6);
+        assert_eq!(all[&(2, 2, 2).into()], 2);
+    }
+}